@@ -0,0 +1,299 @@
+//! contains logic to render obsidian callouts as html admonitions
+//!
+//! supported notations:
+//! - `> [!note]` --> `<div class="callout callout-note">` titled "Note"
+//! - `> [!warning] Custom title` --> callout using the given title
+//! - `> [!tip]- Title` / `> [!tip]+ Title` --> foldable `<details>`, collapsed or expanded
+//! - `> > [!info]` inside another callout --> nested callout
+//!
+//! aliases (e.g. `tldr`, `caution`, `faq`) are mapped to their built-in type,
+//! unknown types are rendered like `note` but keep their name in `data-callout`.
+//! styling is provided by the optional stylesheet created by `write_callout_css`
+
+// external imports
+use regex::Regex;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use toml_edit::{Array, DocumentMut, Item, Table};
+
+pub const CALLOUT_CSS_FILE: &str = "obsidian-callouts.css";
+
+/// matches the first line of a callout: "> [!type]+ title"
+static CALLOUT_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([ \t]*)>[ \t]?\[!([A-Za-z0-9_-]+)\]([+-]?)[ \t]*(.*)$").unwrap());
+
+/// built-in callout types with their aliases and color (as used by obsidian)
+const CALLOUT_TYPES: &[(&str, &[&str], &str)] = &[
+    ("note", &[], "8, 109, 221"),
+    ("abstract", &["summary", "tldr"], "0, 191, 188"),
+    ("info", &[], "8, 109, 221"),
+    ("todo", &[], "8, 109, 221"),
+    ("tip", &["hint", "important"], "0, 191, 188"),
+    ("success", &["check", "done"], "8, 185, 78"),
+    ("question", &["help", "faq"], "236, 117, 0"),
+    ("warning", &["caution", "attention"], "236, 117, 0"),
+    ("failure", &["fail", "missing"], "233, 49, 71"),
+    ("danger", &["error"], "233, 49, 71"),
+    ("bug", &[], "233, 49, 71"),
+    ("example", &[], "120, 82, 238"),
+    ("quote", &["cite"], "158, 158, 158"),
+];
+
+// ---
+// CORE FUNCTIONS
+// ---
+
+/// replaces every callout in the given markdown with html
+/// lines inside fenced code blocks are left untouched
+pub fn convert_callouts(content: &str) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut result = String::with_capacity(content.len());
+    let mut fence: Option<&str> = None;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let trimmed = line.trim_start();
+        index += 1;
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            result.push_str(line);
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker)) {
+            fence = Some(marker);
+            result.push_str(line);
+            continue;
+        }
+        let Some(caps) = CALLOUT_START.captures(line.trim_end_matches(['\n', '\r'])) else {
+            result.push_str(line);
+            continue;
+        };
+
+        // gathering every following line that continues the quote
+        let indent = caps[1].to_string();
+        let mut body = String::new();
+        while index < lines.len() {
+            let Some(quoted) = lines[index]
+                .strip_prefix(indent.as_str())
+                .and_then(|rest| rest.strip_prefix('>'))
+            else {
+                break;
+            };
+            body.push_str(quoted.strip_prefix(' ').unwrap_or(quoted));
+            index += 1;
+        }
+
+        let callout = Callout {
+            kind: &caps[2],
+            fold: &caps[3],
+            title: &caps[4],
+        };
+        // html blocks should not be glued to a preceding paragraph
+        if !result.is_empty() && !result.ends_with("\n\n") {
+            result.push('\n');
+        }
+        result.push_str(&callout.to_html(&convert_callouts(&body), &indent));
+    }
+    result
+}
+
+struct Callout<'a> {
+    kind: &'a str,
+    // "" for static callouts, "+" for expanded and "-" for collapsed ones
+    fold: &'a str,
+    title: &'a str,
+}
+
+impl Callout<'_> {
+    /// renders the callout, body is expected to be converted already
+    /// blank lines are required for mdbook to render markdown inside html blocks
+    fn to_html(&self, body: &str, indent: &str) -> String {
+        let kind = self.kind.to_lowercase();
+        let class = format!("callout callout-{}", canonical_type(&kind));
+        let title = match self.title.trim() {
+            "" => capitalize(&kind),
+            title => escape_html(title),
+        };
+        let body = body.trim_end();
+
+        let (open_tag, title_tag, close_tag) = match self.fold {
+            "" => (
+                format!("<div class=\"{class}\" data-callout=\"{kind}\">"),
+                format!("<div class=\"callout-title\">{title}</div>"),
+                "</div>",
+            ),
+            fold => (
+                format!(
+                    "<details class=\"{class}\" data-callout=\"{kind}\"{}>",
+                    if fold == "+" { " open" } else { "" }
+                ),
+                format!("<summary class=\"callout-title\">{title}</summary>"),
+                "</details>",
+            ),
+        };
+
+        let mut html = format!("{indent}{open_tag}\n{indent}{title_tag}\n");
+        if !body.is_empty() {
+            html.push_str(&format!(
+                "{indent}<div class=\"callout-content\">\n\n{body}\n\n{indent}</div>\n"
+            ));
+        }
+        html.push_str(&format!("{indent}{close_tag}\n\n"));
+        html
+    }
+}
+
+/// maps aliases to their built-in type, unknown types are treated as "note"
+fn canonical_type(kind: &str) -> &'static str {
+    CALLOUT_TYPES
+        .iter()
+        .find(|(name, aliases, _)| *name == kind || aliases.contains(&kind))
+        .map(|(name, _, _)| *name)
+        .unwrap_or("note")
+}
+
+// ---
+// STYLESHEET
+// ---
+
+/// writes the stylesheet for callouts and registers it as `additional-css` in book.toml
+/// the book root is searched upwards from the destination directory
+/// without a book.toml the stylesheet is placed inside the destination
+pub fn write_callout_css(dest_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let book_root = dest_dir
+        .ancestors()
+        .find(|dir| dir.join("book.toml").is_file());
+
+    let Some(book_root) = book_root else {
+        let css_path = dest_dir.join(CALLOUT_CSS_FILE);
+        fs::write(&css_path, callout_css())?;
+        println!(
+            "no book.toml found, add {} to `additional-css` manually",
+            css_path.display()
+        );
+        return Ok(css_path);
+    };
+
+    let css_path = book_root.join(CALLOUT_CSS_FILE);
+    fs::write(&css_path, callout_css())?;
+    register_additional_css(&book_root.join("book.toml"), CALLOUT_CSS_FILE)?;
+    Ok(css_path)
+}
+
+/// adds given stylesheet to `output.html.additional-css`, keeps formatting of book.toml
+fn register_additional_css(book_toml: &Path, css_file: &str) -> Result<(), Box<dyn Error>> {
+    let mut document: DocumentMut = fs::read_to_string(book_toml)?.parse()?;
+
+    let output = document
+        .entry("output")
+        .or_insert_with(implicit_table)
+        .as_table_mut()
+        .ok_or("`output` in book.toml is not a table")?;
+    let html = output
+        .entry("html")
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or("`output.html` in book.toml is not a table")?;
+    let additional_css = html
+        .entry("additional-css")
+        .or_insert_with(|| Item::Value(Array::new().into()))
+        .as_array_mut()
+        .ok_or("`additional-css` in book.toml is not an array")?;
+
+    if additional_css.iter().any(|entry| entry.as_str() == Some(css_file)) {
+        return Ok(());
+    }
+    additional_css.push(css_file);
+    fs::write(book_toml, document.to_string())?;
+    Ok(())
+}
+
+fn implicit_table() -> Item {
+    let mut table = Table::new();
+    table.set_implicit(true);
+    Item::Table(table)
+}
+
+/// creates the stylesheet covering every built-in callout type
+fn callout_css() -> String {
+    let mut css = String::from(
+        "/* generated by obs_to_mdbook, changes will be overwritten */\n\
+.callout {\n  --callout-color: 8, 109, 221;\n  margin: 1em 0;\n  padding: 0.6em 1em;\n  border-left: 4px solid rgb(var(--callout-color));\n  border-radius: 4px;\n  background-color: rgba(var(--callout-color), 0.1);\n}\n\
+.callout-title {\n  font-weight: bold;\n  color: rgb(var(--callout-color));\n}\n\
+details.callout > summary {\n  cursor: pointer;\n}\n\
+.callout-content > :last-child {\n  margin-bottom: 0;\n}\n\
+.obsidian-embed {\n  padding-left: 1em;\n  border-left: 2px solid rgba(158, 158, 158, 0.5);\n}\n",
+    );
+    for (name, _, color) in CALLOUT_TYPES {
+        css.push_str(&format!(".callout-{name} {{\n  --callout-color: {color};\n}}\n"));
+    }
+    css
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+fn capitalize(value: &str) -> String {
+    let mut chars = value.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_static_callouts() {
+        let converted = convert_callouts("text\n> [!tldr] Short <version>\n> body\n> more\nafter\n");
+        assert_eq!(
+            converted,
+            "text\n\n<div class=\"callout callout-abstract\" data-callout=\"tldr\">\n\
+<div class=\"callout-title\">Short &lt;version&gt;</div>\n\
+<div class=\"callout-content\">\n\nbody\nmore\n\n</div>\n</div>\n\nafter\n"
+        );
+    }
+
+    #[test]
+    fn converts_foldable_and_untitled_callouts() {
+        let collapsed = convert_callouts("> [!faq]-\n> answer\n");
+        assert!(collapsed.starts_with("<details class=\"callout callout-question\" data-callout=\"faq\">\n"), "{collapsed}");
+        assert!(collapsed.contains("<summary class=\"callout-title\">Faq</summary>"), "{collapsed}");
+        let expanded = convert_callouts("> [!custom]+ Title\n");
+        assert!(expanded.starts_with("<details class=\"callout callout-note\" data-callout=\"custom\" open>\n"), "{expanded}");
+        assert!(!expanded.contains("callout-content"), "{expanded}");
+    }
+
+    #[test]
+    fn converts_nested_callouts() {
+        let converted = convert_callouts("> [!note] Outer\n> outer text\n> > [!warning] Inner\n> > inner text\n");
+        let outer = converted.find("callout-note").unwrap();
+        let inner = converted.find("callout-warning").unwrap();
+        let inner_text = converted.find("inner text").unwrap();
+        assert!(outer < inner && inner < inner_text, "{converted}");
+        assert!(!converted.lines().any(|line| line.starts_with('>')), "{converted}");
+    }
+
+    #[test]
+    fn keeps_callouts_inside_code_blocks() {
+        let content = "```markdown\n> [!note]\n> body\n```\n~~~\n> [!tip]\n~~~\n";
+        assert_eq!(convert_callouts(content), content);
+    }
+}
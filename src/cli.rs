@@ -0,0 +1,139 @@
+//! contains the command-line interface of the converter
+//!
+//! every path can be supplied as argument, missing ones are requested
+//! interactively on stdin (see `request_paths` in main.rs)
+
+// internal imports
+use crate::dry_run::PlanFormat;
+use crate::ordering::OrderStrategy;
+use crate::slugify::SlugStrategy;
+use crate::summary::SummaryStyle;
+
+// external imports
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "obs_to_mdbook", version, about = "converts an obsidian vault to a valid mdbook representation")]
+pub struct Cli {
+    /// action to perform, defaults to `build`
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// print debug information such as the parsed config and collected paths
+    #[arg(long, short = 'v', global = true)]
+    pub debug: bool,
+
+    /// project file to load, defaults to the nearest `obs2mdbook.toml` in the current directory or its parents
+    #[arg(long, short = 'p', global = true)]
+    pub project: Option<PathBuf>,
+
+    /// stop at the first error instead of reporting every error at the end
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// profile of the project file to build
+    #[arg(long, global = true, conflicts_with = "all_profiles")]
+    pub profile: Option<String>,
+
+    /// build every profile of the project file, one after another
+    #[arg(long, global = true)]
+    pub all_profiles: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// create SUMMARY.md and copy all collected files to the destination
+    Build(PathArgs),
+    /// only create SUMMARY.md
+    Summary(PathArgs),
+    /// only copy collected files to the destination
+    Copy(PathArgs),
+    /// parse config and traverse vault without writing anything
+    Check(PathArgs),
+    /// build, then rebuild whenever the vault or the config note changes
+    Watch(PathArgs),
+    /// run as mdbook preprocessor, reading the book from stdin and writing it to stdout
+    Preprocessor(PreprocessorArgs),
+}
+
+/// arguments passed by mdbook when invoking the preprocessor
+#[derive(Args)]
+pub struct PreprocessorArgs {
+    #[command(subcommand)]
+    pub command: Option<PreprocessorCommand>,
+}
+
+/// subcommands of the preprocessor, without one the book is read from stdin
+#[derive(Subcommand)]
+pub enum PreprocessorCommand {
+    /// exits successfully if the given renderer is supported
+    Supports { renderer: String },
+    /// creates the stylesheet for callouts and registers it in book.toml, run once when setting up the book
+    Css {
+        /// root directory of the book, defaults to the current directory
+        book_dir: Option<PathBuf>,
+    },
+}
+
+/// paths shared by every subcommand
+#[derive(Args, Default)]
+pub struct PathArgs {
+    /// root path of the obsidian vault to traverse
+    pub vault: Option<PathBuf>,
+
+    /// destination directory to copy files to, usually `src/` of the mdbook
+    #[arg(long, short = 'd')]
+    pub dest: Option<PathBuf>,
+
+    /// path to save SUMMARY.md to
+    #[arg(long, short = 's')]
+    pub summary: Option<PathBuf>,
+
+    /// path to the config note inside the vault
+    #[arg(long, short = 'c')]
+    pub config: Option<PathBuf>,
+
+    /// how names of copied files and directories are made url-safe
+    #[arg(long, value_enum)]
+    pub slugify: Option<SlugStrategy>,
+
+    /// layout of SUMMARY.md
+    #[arg(long, value_enum)]
+    pub summary_style: Option<SummaryStyle>,
+
+    /// how chapters and sections are sorted
+    #[arg(long, value_enum)]
+    pub order: Option<OrderStrategy>,
+
+    /// only write changed files and remove files deleted from the vault
+    #[arg(long)]
+    pub sync: bool,
+
+    /// print what would change instead of writing anything, as "text" or "json"
+    #[arg(long, value_enum, num_args = 0..=1, require_equals = true, default_missing_value = "text")]
+    pub dry_run: Option<PlanFormat>,
+}
+
+impl Command {
+    pub fn path_args(&self) -> &PathArgs {
+        match self {
+            Command::Build(args)
+            | Command::Summary(args)
+            | Command::Copy(args)
+            | Command::Check(args)
+            | Command::Watch(args) => args,
+            Command::Preprocessor(_) => unreachable!("the preprocessor does not traverse a vault"),
+        }
+    }
+
+    /// whether the command copies files and thus requires a destination
+    pub fn requires_dest(&self) -> bool {
+        matches!(self, Command::Build(_) | Command::Copy(_) | Command::Watch(_))
+    }
+
+    /// whether the command writes SUMMARY.md and thus requires its path
+    pub fn requires_summary(&self) -> bool {
+        matches!(self, Command::Build(_) | Command::Summary(_) | Command::Watch(_))
+    }
+}
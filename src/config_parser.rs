@@ -1,36 +1,66 @@
-/// contains logic to provide a parser for configurations set in  obsidian-vault
-/// 
-/// the parser takes a path to a viable configuration-file that complies to the following structure: 
-/// 
-/// **the following is omitted** and not parsed:
-/// - "---"
-/// - "# . * "
-/// - "date-*"
-/// - "anchored.*"
-/// - "\n"
-/// 
-/// a valid configuration ought to follow the following structure
-/// 
-/// starting collection of params with:
-/// -> conf-start:ConfigType
-/// followed by a list of values:
-/// - param1
-/// - param2 
-/// ...
-/// **closed by** given string:
-/// -> conf-end:
-/// 
-/// Further everything after "--END-OF-CONFIG--" will not be read and skipped
-/// example can be found in /doc
+//! contains logic to provide a parser for configurations set in  obsidian-vault
+//! 
+//! the parser takes a path to a viable configuration-file that complies to the following structure: 
+//! 
+//! **the following is omitted** and not parsed:
+//! - the yaml frontmatter enclosed by "---", apart from the properties described below
+//! - every line outside of a section (headings, quotes, text)
+//! - lines starting with "#" or ">" and empty lines inside of a section
+//! 
+//! a valid configuration ought to follow the following structure
+//! 
+//! starting collection of params with:
+//! -> conf-start:ConfigType
+//!
+//! followed by a list of values:
+//! - param1
+//! - param2 
+//! - ...
+//!
+//! **closed by** given string:
+//! -> conf-end:
+//! 
+//! Further everything after "--END-OF-CONFIG--" will not be read and skipped
+//! example can be found in /doc
+//!
+//! alternatively every section may be declared as property of the frontmatter,
+//! so that it can be edited in obsidian's property editor:
+//!
+//! ```yaml
+//! ---
+//! excluded_files:
+//!   - secret
+//! dest: ../book/src          # "vault", "dest" and "summary" are added to copy_paths
+//! copy_paths:
+//!   - "notes/lectures: lectures"
+//! ---
+//! ```
+//!
+//! a property takes precedence over body sections of the same type, which are ignored then
+//!
+//! other config notes are included by the `include` section (or property), listing
+//! wikilinks (`- [[base_config]]`) or paths relative to the including note.
+//! included notes are loaded first, so that the lists of the including note come after
+//! theirs and take precedence wherever the last entry wins (negated patterns, paths, prefixes).
+//! every note is loaded once, cyclic includes are errors
+//!
+//! sections of the same type are concatenated. every problem is reported with its
+//! position as "file:line:column", errors (unknown or unterminated sections, empty sections,
+//! lines that are no list item, duplicate keys, unterminated frontmatter) fail the parse, warnings are only printed
 
 // internal imports
+use crate::copy_paths::{DEST_KEY, SUMMARY_KEY, VAULT_KEY};
+use crate::frontmatter::{parse_frontmatter_lines, Frontmatter};
 use crate::structures::{Config,ConfigType};
+use crate::titles::STRIP_RULE;
 
 // external imports
-use std::{error::Error, io::BufReader};
-use std::fs::File;
-use std::io::BufRead;
-use regex::Regex;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 
 
 //  CONFIG-CONSTANTS
@@ -38,123 +68,656 @@ use regex::Regex;
 
 const CONFIG_START: &str = "conf-start:";
 const CONFIG_END: &str =  "conf-end:";
+const END_OF_CONFIG: &str = "--END-OF-CONFIG--";
+const FRONTMATTER_DELIMITER: &str = "---";
 const CONF_EXCLUDED_FILES: &str = "excluded_files";
 const CONF_INCLUDED_DIRECTORIES: &str = "included_directories";
+const CONF_EXCLUDED_DIRECTORIES: &str = "excluded_directories";
 const CONF_PREFIXES: &str = "prefixes_for_headlines";
 const CONF_COLLECTED_PATHS: &str = "copy_paths";
+const CONF_CHAPTER_ORDER: &str = "chapter_order";
+const CONF_INCLUDE: &str = "include";
+const SECTION_TYPES: [&str; 6] = [
+    CONF_EXCLUDED_FILES,
+    CONF_INCLUDED_DIRECTORIES,
+    CONF_EXCLUDED_DIRECTORIES,
+    CONF_PREFIXES,
+    CONF_COLLECTED_PATHS,
+    CONF_CHAPTER_ORDER,
+];
+/// every section name accepted by the parser
+const KNOWN_SECTIONS: [&str; 7] = [
+    CONF_EXCLUDED_FILES,
+    CONF_INCLUDED_DIRECTORIES,
+    CONF_EXCLUDED_DIRECTORIES,
+    CONF_PREFIXES,
+    CONF_COLLECTED_PATHS,
+    CONF_CHAPTER_ORDER,
+    CONF_INCLUDE,
+];
+/// unknown section types within this edit distance of a known one are suggested
+const MAX_SUGGESTION_DISTANCE: usize = 3;
 
-/// --- 
-/// CORE FUNCTIONS
-/// ---
-
-/// FIXME naming lol
-/// creates Config from given param
-/// if no conf-end was received --> will return error, signaling wrong formatting
-fn assemble_single_config(config_type:ConfigType,param_vec:Vec<String>) -> Result<Config,Box<dyn Error>> {
-
-    let resulting_params: Vec<String> = param_vec
-                            .iter()
-                            .take_while(| &val| val != CONFIG_END)
-                            .map(|val| val.to_string().replace("- ", ""))
-                            .collect();
-    if resulting_params.is_empty() { 
-        return Err("no params found before conf-end: --> typo?".into())
-    };
-    // processed correctly 
-    Ok(Config{
-        conf_type: config_type,
-        collection_of_options: resulting_params
-    })
-}
-
-
-/// fn construct_excluded_files(&filtered_config:Iterator<Item = <Result<>>) -> structures::ExcludedFiles {
-/// with given iterator we search for "start" and end 
-/// }
-/// FIXME Improve structure of collecting 
-fn vec_to_config(config_as_list:Vec<String>) -> Result<Vec<Config>,Box<dyn Error>> {
-
-    // iterate through vector
-    let regex_start = Regex::new(r"conf-start:.*").unwrap();
-
-    let mut collection: Vec<Config> = Vec::new();
-
-    for entry in &config_as_list{ 
-
-        if regex_start.is_match(entry.as_str()){
-            println!("found start with {entry}");
-            // extracting type from string: 
-            let type_as_string = entry.replace(CONFIG_START, "");
-            let option_type = match type_as_string.as_str() { 
-                CONF_EXCLUDED_FILES => ConfigType::ExcludedFiles,
-                CONF_INCLUDED_DIRECTORIES => ConfigType::IncludedDirectories,
-                CONF_PREFIXES => ConfigType::PrefixHeadline,
-                CONF_COLLECTED_PATHS => ConfigType::CollectedPaths,
-                _ => return Err(format!("no matching config-param was supplied {type_as_string}").into()),
-            };
-            // gathering params for given part
-            let rest_without_start: Vec<String> = config_as_list.split(|value| value == entry)
-            .nth(1)
-            .unwrap_or(&[])
-            .to_vec();
-
-            let resulting_confg = assemble_single_config(option_type, rest_without_start);
-            match resulting_confg{
-                Ok(config) => collection.push(config),
-                Err(e) => return Err(format!("error parsing config\n {e}").into())
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// single problem found in the config note, lines and columns start at 1
+pub struct ConfigDiagnostic {
+    pub severity: Severity,
+    pub origin: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+/// every error found while parsing, returned as a whole so all of them can be fixed at once
+#[derive(Debug)]
+pub struct ConfigErrors(Vec<String>);
+
+/// sections of a single config note and the notes it includes
+pub struct ParsedNote {
+    pub configs: Vec<Config>,
+    pub includes: Vec<Include>,
+}
+
+/// Configs of a config note and every note it includes
+pub struct LoadedConfiguration {
+    pub configs: Vec<Config>,
+    // canonical paths of every loaded note, the including one first
+    pub notes: Vec<PathBuf>,
+}
+
+/// entry of the `include` section, with its position for diagnostics
+pub struct Include {
+    pub target: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// section currently being collected
+struct OpenSection {
+    // None for sections not resulting in a Config: `include` and unknown ones
+    conf_type: Option<ConfigType>,
+    name: String,
+    line: usize,
+    options: Vec<String>,
+}
+
+/// collects sections and problems line by line
+struct Parser<'a> {
+    origin: &'a Path,
+    configs: Vec<Config>,
+    diagnostics: Vec<ConfigDiagnostic>,
+    open: Option<OpenSection>,
+    // first line of every key per section type, to detect duplicates
+    keys: HashMap<(String, String), usize>,
+    // section types declared in the frontmatter, replacing sections of the body
+    declared_properties: Vec<&'static str>,
+    includes: Vec<Include>,
+}
+
+// --- 
+// CORE FUNCTIONS
+// ---
+
+/// loads the given config note and every note it includes, returns their Configs and paths
+/// lists of included notes come first, see module documentation
+pub fn load_configuration(path:&Path) -> Result<LoadedConfiguration,Box<dyn Error>> {
+    let mut configs = Vec::new();
+    let mut notes = Vec::new();
+    load_with_includes(path, &mut Vec::new(), &mut notes, &mut configs)?;
+    eprintln!("finished parsing config!");
+    Ok(LoadedConfiguration { configs, notes })
+}
+
+/// iterates through the note line by line, parses Configs and returns them with the includes
+/// warnings are printed, any error fails the whole parse
+/// `origin` is only used to denote the position of problems
+pub fn parse_configuration(file_buffer:impl BufRead, origin:&Path) -> Result<ParsedNote,Box<dyn Error>> { 
+    let mut parser = Parser::new(origin);
+    let mut frontmatter_lines: Option<Vec<String>> = None;
+
+    for (index, line) in file_buffer.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        let trimmed = line.trim();
+
+        // frontmatter is only recognized at the very start of the note
+        if line_number == 1 && trimmed == FRONTMATTER_DELIMITER {
+            frontmatter_lines = Some(Vec::new());
+            continue;
+        }
+        if let Some(lines) = frontmatter_lines.as_mut() {
+            if trimmed != FRONTMATTER_DELIMITER {
+                lines.push(line);
+                continue;
             }
+            parser.declare_properties(&parse_frontmatter_lines(lines), lines);
+            frontmatter_lines = None;
+            continue;
         }
-
+        if trimmed == END_OF_CONFIG {
+            break;
+        }
+        parser.parse_line(&line, line_number);
+    }
+    if frontmatter_lines.is_some() {
+        parser.push(Severity::Error, 1, 1, "frontmatter is not terminated".to_string(),
+            Some(format!("add {FRONTMATTER_DELIMITER} after the last property")));
     }
-    println!("finished parsing config!");
-    Ok(collection)
+    parser.finish()
 }
 
-/// iterates through File, parses Configs and returns them as Vector
-/// everything matching the following RegEx is not traversed and ignored:
-/// Blacklist "---.*|#.*|date-*|anchored.*|>.*|^\s*$"
-/// 
-pub fn parse_configuration(file_buffer:BufReader<File>) -> Result<Vec<Config>,Box<dyn Error>> { 
+impl<'a> Parser<'a> {
+    fn new(origin: &'a Path) -> Parser<'a> {
+        Parser {
+            origin,
+            configs: Vec::new(),
+            diagnostics: Vec::new(),
+            open: None,
+            keys: HashMap::new(),
+            declared_properties: Vec::new(),
+            includes: Vec::new(),
+        }
+    }
 
-    let blacklist = Regex::new(r"---.*|#.*|date-*|anchored.*|>.*|^\s*$").unwrap();
-    let filtered_config: Vec<String> = file_buffer.lines()
-        .filter_map(|line| match line { 
-            Ok(line) => { 
-                if !blacklist.is_match(&line) {
-                    Some(line)
-                } else { 
-                    None 
+    /// collects every section declared as property of the frontmatter
+    /// "vault", "dest" and "summary" are entries of copy_paths
+    /// `lines` are the ones of the frontmatter, to locate includes
+    fn declare_properties(&mut self, frontmatter: &Frontmatter, lines: &[String]) {
+        for target in frontmatter.get_list(CONF_INCLUDE) {
+            // the frontmatter starts at the second line of the note
+            let (line, column) = lines
+                .iter()
+                .enumerate()
+                .find_map(|(index, line)| line.find(target).map(|position| (index + 2, position + 1)))
+                .unwrap_or((1, 1));
+            self.includes.push(Include { target: target.trim().to_string(), line, column });
+        }
+        if !self.includes.is_empty() {
+            eprintln!("found property {CONF_INCLUDE}");
+            self.declared_properties.push(CONF_INCLUDE);
+        }
+        for name in SECTION_TYPES {
+            let mut options: Vec<String> = Vec::new();
+            if name == CONF_COLLECTED_PATHS {
+                for key in [VAULT_KEY, DEST_KEY, SUMMARY_KEY] {
+                    if let Some(value) = frontmatter.get_text(key).filter(|value| !value.is_empty()) {
+                        options.push(format!("{key}: {value}"));
+                    }
                 }
             }
-            Err(_) => None,
+            options.extend(frontmatter.get_list(name).into_iter().map(|value| value.trim().to_string()));
+            let Some(conf_type) = section_type(name).filter(|_| !options.is_empty()) else {
+                continue;
+            };
+            eprintln!("found property {name}");
+            self.declared_properties.push(name);
+            self.configs.push(Config {
+                conf_type,
+                collection_of_options: options,
+            });
+        }
+    }
+
+    fn parse_line(&mut self, line: &str, line_number: usize) {
+        let trimmed = line.trim();
+        let column = line.len() - line.trim_start().len() + 1;
+
+        if let Some(type_as_string) = trimmed.strip_prefix(CONFIG_START) {
+            let type_column = column + trimmed.len() - type_as_string.trim_start().len();
+            self.start_section(type_as_string.trim(), line_number, type_column);
+            return;
+        }
+        if trimmed == CONFIG_END {
+            match self.open.take() {
+                Some(section) => self.close_section(section, line_number, column),
+                None => self.push(Severity::Warning, line_number, column,
+                    format!("{CONFIG_END} without matching {CONFIG_START}, ignored"),
+                    Some(format!("remove it or add a {CONFIG_START}<type> above"))),
+            }
+            return;
+        }
+
+        let is_list_item = trimmed == "-" || trimmed.starts_with("- ");
+        let Some(section) = self.open.as_mut() else {
+            if is_list_item {
+                self.push(Severity::Warning, line_number, column,
+                    "list item outside of a section, ignored".to_string(),
+                    Some(format!("move it between {CONFIG_START}<type> and {CONFIG_END}")));
+            }
+            return;
+        };
+        // comments and blank lines are allowed inside of sections
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('>') {
+            return;
+        }
+        if !is_list_item {
+            let message = format!("expected a list item in section \"{}\" (opened at line {})", section.name, section.line);
+            self.push(Severity::Error, line_number, column, message, Some(format!("write it as \"- {trimmed}\"")));
+            return;
+        }
+        let value = trimmed[1..].trim();
+        // skipping empty list items such as "- "
+        if value.is_empty() {
+            return;
+        }
+        let value_column = column + trimmed.find(value).unwrap_or(0);
+        self.check_duplicate(value, line_number, value_column);
+        if let Some(section) = self.open.as_mut() {
+            section.options.push(value.to_string());
+            if section.name == CONF_INCLUDE && !self.declared_properties.contains(&CONF_INCLUDE) {
+                self.includes.push(Include { target: value.to_string(), line: line_number, column: value_column });
+            }
+        }
+    }
+
+    fn start_section(&mut self, type_as_string: &str, line_number: usize, column: usize) {
+        if let Some(section) = self.open.take() {
+            let message = format!("section \"{}\" is not terminated", section.name);
+            self.push(Severity::Error, section.line, 1, message,
+                Some(format!("add {CONFIG_END} before line {line_number}")));
+        }
+        eprintln!("found start with {CONFIG_START}{type_as_string}");
+        let conf_type = section_type(type_as_string);
+        // included notes are collected while parsing its entries
+        if conf_type.is_none() && type_as_string != CONF_INCLUDE {
+            let suggestion = match closest_section_type(type_as_string) {
+                Some(known) => format!("did you mean \"{CONFIG_START}{known}\"?"),
+                None => format!("known types are: {}", KNOWN_SECTIONS.join(", ")),
+            };
+            let message = format!("unknown section type \"{type_as_string}\"");
+            // entries of the unknown section are still checked, but never used
+            self.push(Severity::Error, line_number, column, message, Some(suggestion));
+        }
+        self.open = Some(OpenSection {
+            conf_type,
+            name: type_as_string.to_string(),
+            line: line_number,
+            options: Vec::new(),
+        });
+    }
+
+    fn close_section(&mut self, section: OpenSection, line_number: usize, column: usize) {
+        if section.options.is_empty() {
+            let message = format!("section \"{}\" opened at line {} has no entries", section.name, section.line);
+            self.push(Severity::Error, line_number, column, message, Some("add entries as \"- value\" or remove the section".to_string()));
+            return;
+        }
+        if self.declared_properties.contains(&section.name.as_str()) {
+            let message = format!("section \"{}\" is ignored, the property of the same name takes precedence", section.name);
+            self.push(Severity::Warning, section.line, 1, message, Some("remove either the section or the property".to_string()));
+            return;
+        }
+        if let Some(conf_type) = section.conf_type {
+            self.configs.push(Config {
+                conf_type,
+                collection_of_options: section.options,
+            });
+        }
+    }
+
+    /// entries of `key: value` sections must not repeat their key, other entries should not repeat at all
+    fn check_duplicate(&mut self, value: &str, line_number: usize, column: usize) {
+        let Some(section) = &self.open else { return };
+        let key = match section.name.as_str() {
+            CONF_COLLECTED_PATHS | CONF_PREFIXES => value.split_once(':').map(|(key, _)| key.trim()),
+            _ => None,
+        };
+        // several strip rules are applied one after another
+        if key == Some(STRIP_RULE) && section.name == CONF_PREFIXES {
+            return;
+        }
+        let entry = (section.name.clone(), key.unwrap_or(value).to_string());
+        let Some(first_line) = self.keys.get(&entry).copied() else {
+            self.keys.insert(entry, line_number);
+            return;
+        };
+        match key {
+            Some(key) => self.push(Severity::Error, line_number, column,
+                format!("duplicate key \"{key}\" in section \"{}\"", entry.0),
+                Some(format!("it is already set at line {first_line}, remove one of them"))),
+            None => self.push(Severity::Warning, line_number, column,
+                format!("duplicate entry \"{value}\" in section \"{}\"", entry.0),
+                Some(format!("it is already listed at line {first_line}"))),
+        }
+    }
+
+    fn push(&mut self, severity: Severity, line: usize, column: usize, message: String, suggestion: Option<String>) {
+        self.diagnostics.push(ConfigDiagnostic {
+            severity,
+            origin: self.origin.to_path_buf(),
+            line,
+            column,
+            message,
+            suggestion,
+        });
+    }
+
+    /// reports a section left open, prints warnings and returns every error
+    fn finish(mut self) -> Result<ParsedNote, Box<dyn Error>> {
+        if let Some(section) = self.open.take() {
+            let message = format!("section \"{}\" is not terminated", section.name);
+            self.push(Severity::Error, section.line, 1, message,
+                Some(format!("add {CONFIG_END} after its last entry")));
+        }
+        self.diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+        let (errors, warnings): (Vec<_>, Vec<_>) = self
+            .diagnostics
+            .into_iter()
+            .partition(|diagnostic| diagnostic.severity == Severity::Error);
+        for warning in &warnings {
+            eprintln!("{warning}");
+        }
+        if !errors.is_empty() {
+            return Err(ConfigErrors(errors.iter().map(ToString::to_string).collect()).into());
+        }
+        Ok(ParsedNote {
+            configs: self.configs,
+            includes: self.includes,
         })
-        .collect();
-    // processing filtered config!
-    let parsed_config = vec_to_config(filtered_config);
-    match parsed_config{
-        Ok(configurations) => return Ok(configurations),
-        Err(e) => return Err(format!("error converting, see: {e}").into())
-    };
+    }
 }
 
-/// ---- 
-/// HELPER FUNCTIONS
-/// ----
+// ---- 
+// HELPER FUNCTIONS
+// ----
 
-pub fn print_config(configs: &Vec<Config>) -> () { 
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}:{}:{}: {severity}: {}", self.origin.display(), self.line, self.column, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n  help: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error(s) in config note\n{}", self.0.len(), self.0.join("\n"))
+    }
+}
+
+impl Error for ConfigErrors {}
+
+/// loads the note after every note it includes, `chain` holds the notes currently being loaded
+/// notes already loaded through another include are skipped
+fn load_with_includes(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+    loaded: &mut Vec<PathBuf>,
+    configs: &mut Vec<Config>,
+) -> Result<(), Box<dyn Error>> {
+    let canonical = path.canonicalize().map_err(|e| format!("could not open config note {}\n {e}", path.display()))?;
+    // included notes resolve their paths against this one, absolute so they are not resolved twice
+    let canonical_dir = canonical.parent().unwrap_or(Path::new("")).to_path_buf();
+    chain.push(canonical.clone());
+    loaded.push(canonical);
+    let note = parse_configuration(BufReader::new(File::open(path)?), path)?;
+    let note_dir = path.parent().unwrap_or(Path::new(""));
+
+    for include in &note.includes {
+        let include_error = |message: String, suggestion: String| ConfigErrors(vec![ConfigDiagnostic {
+            severity: Severity::Error,
+            origin: path.to_path_buf(),
+            line: include.line,
+            column: include.column,
+            message,
+            suggestion: Some(suggestion),
+        }.to_string()]);
+        let Some(target) = resolve_include(note_dir, &include.target) else {
+            return Err(include_error(
+                format!("could not find included note \"{}\"", include.target),
+                "use a wikilink to a note next to this one or above it, or a relative path".to_string(),
+            ).into());
+        };
+        let canonical_target = target.canonicalize()?;
+        if chain.contains(&canonical_target) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .skip_while(|note| **note != canonical_target)
+                .chain([&canonical_target])
+                .map(|note| note.display().to_string())
+                .collect();
+            return Err(include_error(
+                format!("cyclic include of \"{}\"", include.target),
+                format!("remove one include of: {}", cycle.join(" -> ")),
+            ).into());
+        }
+        if loaded.contains(&canonical_target) {
+            continue;
+        }
+        eprintln!("including config note {}", target.display());
+        load_with_includes(&target, chain, loaded, configs)?;
+    }
+
+    let is_included = chain.len() > 1;
+    configs.extend(note.configs.into_iter().map(|config| match is_included {
+        true => anchor_copy_paths(config, &canonical_dir),
+        false => config,
+    }));
+    chain.pop();
+    Ok(())
+}
+
+/// returns the note an include points to
+/// "[[base_config|alias]]" --> "base_config.md" next to the including note, in one of its parent directories
+/// or - closest first - in one of its subdirectories
+fn resolve_include(note_dir: &Path, target: &str) -> Option<PathBuf> {
+    let target = target.trim();
+    let link = target.strip_prefix("[[").and_then(|link| link.strip_suffix("]]"));
+    let target = link
+        .map(|link| link.split(['|', '#']).next().unwrap_or(link).trim())
+        .unwrap_or(target);
+    if target.is_empty() {
+        return None;
+    }
+    let mut relative = PathBuf::from(target);
+    if relative.extension().is_none() {
+        relative.set_extension("md");
+    }
+    match link {
+        Some(_) => note_dir
+            .ancestors()
+            .map(|dir| dir.join(&relative))
+            .find(|candidate| candidate.is_file())
+            .or_else(|| find_below(note_dir, &relative)),
+        None => Some(note_dir.join(relative)).filter(|candidate| candidate.is_file()),
+    }
+}
+
+/// breadth-first search for the relative path below the given directory, hidden directories are skipped
+fn find_below(dir: &Path, relative: &Path) -> Option<PathBuf> {
+    let mut queue = VecDeque::from([dir.to_path_buf()]);
+    while let Some(current) = queue.pop_front() {
+        let candidate = current.join(relative);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        let Ok(entries) = fs::read_dir(&current) else { continue };
+        let mut sub_directories: Vec<PathBuf> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        sub_directories.sort();
+        queue.extend(sub_directories);
+    }
+    None
+}
+
+/// resolves the paths of copy_paths against the included note, as they are relative to it
+/// `note_dir` is absolute, so the including note does not resolve them against its own directory again
+/// EXAMPLE:
+/// "dest: ../book/src" in /vault/shared/base.md --> "dest: /vault/shared/../book/src"
+fn anchor_copy_paths(mut config: Config, note_dir: &Path) -> Config {
+    if !matches!(config.conf_type, ConfigType::CollectedPaths) {
+        return config;
+    }
+    for entry in config.collection_of_options.iter_mut() {
+        let Some((key, value)) = entry.split_once(':') else { continue };
+        if [VAULT_KEY, DEST_KEY, SUMMARY_KEY].contains(&key.trim()) {
+            *entry = format!("{}: {}", key.trim(), note_dir.join(value.trim()).display());
+        }
+    }
+    config
+}
+
+fn section_type(type_as_string: &str) -> Option<ConfigType> {
+    match type_as_string {
+        CONF_EXCLUDED_FILES => Some(ConfigType::ExcludedFiles),
+        CONF_INCLUDED_DIRECTORIES => Some(ConfigType::IncludedDirectories),
+        CONF_EXCLUDED_DIRECTORIES => Some(ConfigType::ExcludedDirectories),
+        CONF_PREFIXES => Some(ConfigType::PrefixHeadline),
+        CONF_COLLECTED_PATHS => Some(ConfigType::CollectedPaths),
+        CONF_CHAPTER_ORDER => Some(ConfigType::ChapterOrder),
+        _ => None,
+    }
+}
+
+/// known section type closest to the given one, if it is close enough to be a typo
+fn closest_section_type(type_as_string: &str) -> Option<&'static str> {
+    closest_match(type_as_string, &KNOWN_SECTIONS)
+}
+
+/// known name closest to the given one, if it is close enough to be a typo
+pub fn closest_match<'a>(name: &str, known_names: &[&'a str]) -> Option<&'a str> {
+    known_names
+        .iter()
+        .map(|known| (edit_distance(&name.to_lowercase(), known), *known))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min()
+        .map(|(_, known)| known)
+}
+
+/// levenshtein distance between two strings
+/// EXAMPLE:
+/// "exclude_files", "excluded_files" --> 1
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, char_a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(char_a != *char_b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+pub fn print_config(configs: &[Config]) { 
     for config in configs{
 
         let as_string = match config.conf_type{
             ConfigType::ExcludedFiles => "Excluded files",
             ConfigType::IncludedDirectories => "included directories",
+            ConfigType::ExcludedDirectories => "excluded directories",
             ConfigType::PrefixHeadline => "headline prefixes",
-            ConfigType::CollectedPaths => "paths to copy to"
+            ConfigType::CollectedPaths => "paths to copy to",
+            ConfigType::ChapterOrder => "chapter order",
         };
-        println!("extracted config of type: {as_string}");
+        eprintln!("extracted config of type: {as_string}");
         for entry in &config.collection_of_options{
-            println!("-> {entry}");
+            eprintln!("-> {entry}");
         };
-        println!();
+        eprintln!();
+    }
+    eprintln!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::copy_paths::CopyPaths;
+    use std::io::Cursor;
+
+    fn parse(content: &str) -> Result<ParsedNote, String> {
+        parse_configuration(Cursor::new(content), Path::new("config.md")).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn resolves_paths_of_included_notes_once() {
+        let root = std::env::temp_dir().join(format!("obs2mdbook-relative-include-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("vault/shared")).unwrap();
+        fs::create_dir_all(root.join("book/src")).unwrap();
+        fs::write(root.join("vault/config.md"), "conf-start:include\n- [[base]]\nconf-end:\n").unwrap();
+        fs::write(root.join("vault/shared/base.md"), "conf-start:copy_paths\n- dest: ../../book/src\nconf-end:\n").unwrap();
+
+        // as with `build vault --config vault/config.md`
+        std::env::set_current_dir(&root).unwrap();
+        let config_note = Path::new("vault/config.md");
+        let loaded = load_configuration(config_note).unwrap();
+        let copy_paths = CopyPaths::from_configs(&loaded.configs, config_note.parent().unwrap()).unwrap();
+        let dest = copy_paths.dest.unwrap().canonicalize().unwrap();
+        let book_src = root.join("book/src").canonicalize().unwrap();
+        std::env::set_current_dir(std::env::temp_dir()).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(dest, book_src);
+        assert_eq!(loaded.notes.len(), 2);
+    }
+
+    #[test]
+    fn parses_sections_and_includes() {
+        let note = parse(
+            "# Config\nconf-start:include\n- [[base]]\nconf-end:\nconf-start:excluded_files\n- secret\n# comment\n- drafts/\nconf-end:\n--END-OF-CONFIG--\nconf-start:unknown\n",
+        )
+        .unwrap();
+        assert_eq!(note.configs.len(), 1);
+        assert!(matches!(note.configs[0].conf_type, ConfigType::ExcludedFiles));
+        assert_eq!(note.configs[0].collection_of_options, vec!["secret", "drafts/"]);
+        assert_eq!(note.includes.len(), 1);
+        assert_eq!((note.includes[0].target.as_str(), note.includes[0].line, note.includes[0].column), ("[[base]]", 3, 3));
+    }
+
+    #[test]
+    fn reports_unknown_sections_with_suggestion() {
+        let error = parse("conf-start:exclued_files\n- secret\nconf-end:\n").err().unwrap();
+        assert!(error.contains("config.md:1:12: error: unknown section type \"exclued_files\""), "{error}");
+        assert!(error.contains("did you mean \"conf-start:excluded_files\"?"), "{error}");
+    }
+
+    #[test]
+    fn reports_every_error_at_once() {
+        let error = parse("conf-start:excluded_files\nsecret\nconf-start:copy_paths\n- dest: a\n- dest: b\nconf-end:\nconf-start:chapter_order\nconf-end:\n")
+            .err()
+            .unwrap();
+        assert!(error.starts_with("4 error(s) in config note"), "{error}");
+        assert!(error.contains("config.md:1:1: error: section \"excluded_files\" is not terminated"), "{error}");
+        assert!(error.contains("config.md:2:1: error: expected a list item"), "{error}");
+        assert!(error.contains("config.md:5:3: error: duplicate key \"dest\""), "{error}");
+        assert!(error.contains("config.md:8:1: error: section \"chapter_order\" opened at line 7 has no entries"), "{error}");
+    }
+
+    #[test]
+    fn reports_unterminated_frontmatter() {
+        let error = parse("---\ntags: [config]\nconf-start:excluded_files\n- secret\nconf-end:\n").err().unwrap();
+        assert!(error.contains("config.md:1:1: error: frontmatter is not terminated"), "{error}");
+    }
+
+    #[test]
+    fn warnings_do_not_fail_the_parse() {
+        let note = parse("- stray\nconf-end:\nconf-start:excluded_files\n- secret\n- secret\nconf-end:\n").unwrap();
+        assert_eq!(note.configs[0].collection_of_options, vec!["secret", "secret"]);
+    }
+
+    #[test]
+    fn properties_replace_sections_of_the_same_type() {
+        let note = parse("---\nexcluded_files: [secret]\ndest: ../book/src\n---\nconf-start:excluded_files\n- other\nconf-end:\n").unwrap();
+        let options: Vec<&Vec<String>> = note.configs.iter().map(|config| &config.collection_of_options).collect();
+        assert_eq!(options, vec![&vec!["secret".to_string()], &vec!["dest: ../book/src".to_string()]]);
     }
-    println!();
 }
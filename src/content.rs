@@ -0,0 +1,318 @@
+//! contains the content-rewriting stage applied to every markdown file before copying
+//!
+//! each converter receives only text outside of fenced code blocks and inline code,
+//! so that obsidian-syntax used as example inside code is left untouched.
+//! converters collect their warnings (unresolved links, cyclic embeds, ...) in the context,
+//! they are reported as warnings once the note is converted, see diagnostics.rs
+
+// internal imports
+use crate::callouts::convert_callouts;
+use crate::diagnostics::ObsError;
+use crate::link_resolver::LinkIndex;
+use crate::wikilinks::convert_wikilinks;
+
+// external imports
+use regex::Regex;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+static INLINE_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"``[^\n]*?``|`[^`\n]*`").unwrap());
+/// block ids ("^block-id") at the end of a line are only used as link targets by obsidian
+static BLOCK_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)[ \t]+\^[A-Za-z0-9-]+[ \t]*$").unwrap());
+
+/// settings of the content-rewriting stage
+/// may be set in the `[content]` section of the project file
+#[derive(Deserialize, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentSettings {
+    pub pdf_embed: PdfEmbed,
+    // whether to create a stylesheet for callouts and register it in book.toml
+    pub callout_css: bool,
+}
+
+/// how embedded pdfs (`![[file.pdf]]`) are rendered
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PdfEmbed {
+    // inline viewer using an `<embed>` tag
+    #[default]
+    Embed,
+    // plain link to the copied file
+    Link,
+}
+
+/// converted notes of earlier runs, used by `watch` to convert only changed notes
+/// a note is converted again if its source changed or if it embeds other notes.
+/// every entry is dropped if the collected files change, as links may resolve differently
+#[derive(Default)]
+pub struct ConversionCache {
+    // vault-relative paths of every file links may resolve to
+    structure: Vec<PathBuf>,
+    entries: HashMap<PathBuf, CachedNote>,
+}
+
+struct CachedNote {
+    size: u64,
+    modified: Option<SystemTime>,
+    content: String,
+}
+
+/// information required while converting a single file
+pub struct ConversionContext<'a> {
+    pub index: &'a LinkIndex,
+    pub settings: &'a ContentSettings,
+    // vault-relative path of the file whose content is converted
+    // links are resolved starting from here
+    pub source: &'a Path,
+    // destination-relative path of the file the converted content ends up in
+    // links are created relative to it
+    pub output: &'a Path,
+    // notes (and sections) currently being transcluded, used to detect cycles
+    pub embed_chain: Vec<String>,
+    // problems found while converting, shared with the contexts of transcluded notes
+    pub warnings: &'a RefCell<Vec<ObsError>>,
+}
+
+impl<'a> ConversionContext<'a> {
+    pub fn new(
+        index: &'a LinkIndex,
+        settings: &'a ContentSettings,
+        source: &'a Path,
+        output: &'a Path,
+        warnings: &'a RefCell<Vec<ObsError>>,
+    ) -> ConversionContext<'a> {
+        ConversionContext {
+            index,
+            settings,
+            source,
+            output,
+            embed_chain: vec![embed_key(source, None)],
+            warnings,
+        }
+    }
+
+    /// records a problem of the converted file, the conversion itself continues
+    pub fn warn(&self, message: impl fmt::Display) {
+        self.warnings.borrow_mut().push(ObsError::conversion(message).at(self.source));
+    }
+
+    /// whether the converted content is transcluded into another note
+    pub fn is_transcluded(&self) -> bool {
+        self.embed_chain.len() > 1
+    }
+}
+
+impl ConversionCache {
+    /// drops every entry if the files links may resolve to changed
+    pub fn validate(&mut self, structure: Vec<PathBuf>) {
+        if self.structure != structure {
+            self.entries.clear();
+            self.structure = structure;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.structure.clear();
+    }
+
+    /// returns the converted note if its source did not change since
+    pub fn get(&self, original_path: &Path) -> Option<&str> {
+        let cached = self.entries.get(original_path)?;
+        let (size, modified) = source_state(original_path)?;
+        (cached.size == size && cached.modified == modified).then_some(cached.content.as_str())
+    }
+
+    /// stores the converted note, notes embedding others are never stored
+    pub fn insert(&mut self, original_path: &Path, source: &str, converted: &str) {
+        if source.contains("![[") {
+            return;
+        }
+        if let Some((size, modified)) = source_state(original_path) {
+            self.entries.insert(
+                original_path.to_path_buf(),
+                CachedNote { size, modified, content: converted.to_string() },
+            );
+        }
+    }
+}
+
+fn source_state(original_path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let metadata = fs::metadata(original_path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
+}
+
+/// applies every conversion to the content of a markdown file
+/// the frontmatter of the note is removed, as mdbook would render it as text.
+/// callouts are converted last, as they operate on whole lines instead of text spans
+pub fn convert_markdown(content: &str, context: &ConversionContext) -> String {
+    // transcluded excerpts are stripped before extracting sections, see embeds.rs
+    let content = match context.is_transcluded() {
+        true => content,
+        false => strip_frontmatter(content),
+    };
+    let converted = map_outside_code(content, |text| {
+        let without_block_ids = BLOCK_ID.replace_all(text, "");
+        convert_wikilinks(&without_block_ids, context)
+    });
+    convert_callouts(&converted)
+}
+
+/// identifies a transcluded note or a section of it
+pub fn embed_key(path: &Path, heading: Option<&str>) -> String {
+    format!("{}#{}", path.display(), heading.unwrap_or(""))
+}
+
+/// removes the yaml frontmatter enclosed by "---" at the start of a note
+pub fn strip_frontmatter(content: &str) -> &str {
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return content;
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return &rest[offset..];
+        }
+    }
+    content
+}
+
+/// applies given function to every part of the content that is not code
+/// fenced code blocks are detected by lines starting with "```" or "~~~",
+/// also inside of quotes and callouts ("> ```")
+pub fn map_outside_code(content: &str, convert: impl Fn(&str) -> String) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut text = String::new();
+    let mut fence: Option<&str> = None;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '>');
+        match fence {
+            Some(marker) => {
+                result.push_str(line);
+                if trimmed.starts_with(marker) {
+                    fence = None;
+                }
+            }
+            None => {
+                let marker = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker));
+                if let Some(marker) = marker {
+                    result.push_str(&map_outside_inline_code(&text, &convert));
+                    text.clear();
+                    result.push_str(line);
+                    fence = Some(marker);
+                } else {
+                    text.push_str(line);
+                }
+            }
+        }
+    }
+    result.push_str(&map_outside_inline_code(&text, &convert));
+    result
+}
+
+fn map_outside_inline_code(text: &str, convert: &impl Fn(&str) -> String) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for code in INLINE_CODE.find_iter(text) {
+        result.push_str(&convert(&text[last_end..code.start()]));
+        result.push_str(code.as_str());
+        last_end = code.end();
+    }
+    result.push_str(&convert(&text[last_end..]));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeds::MAX_EMBED_DEPTH;
+
+    /// vault in a fresh temporary directory, containing the given notes
+    fn vault(name: &str, notes: &[(&str, &str)]) -> (PathBuf, LinkIndex) {
+        let root = std::env::temp_dir().join(format!("obs2mdbook-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for (path, content) in notes {
+            fs::write(root.join(path), content).unwrap();
+        }
+        let paths = notes.iter().map(|(path, _)| PathBuf::from(path)).collect();
+        let index = LinkIndex::from_files(&root, paths);
+        (root, index)
+    }
+
+    #[test]
+    fn transclusion_inside_callout_stays_inside() {
+        let note = "> [!note] Title\n> ![[Other]]\n> after\n";
+        let (root, index) = vault("callout-embed", &[("Note.md", note), ("Other.md", "embedded text\n")]);
+        let settings = ContentSettings::default();
+        let warnings = RefCell::new(Vec::new());
+        let context = ConversionContext::new(&index, &settings, Path::new("Note.md"), Path::new("Note.md"), &warnings);
+
+        let converted = convert_markdown(note, &context);
+        fs::remove_dir_all(root).unwrap();
+
+        let content_start = converted.find("callout-content").unwrap();
+        let embedded = converted.find("embedded text").unwrap();
+        let after = converted.find("after").unwrap();
+        let close = converted.rfind("</div>").unwrap();
+        assert!(content_start < embedded && embedded < after && after < close, "{converted}");
+        assert!(!converted.lines().any(|line| line.starts_with('>')), "{converted}");
+        assert!(warnings.borrow().is_empty());
+    }
+
+    #[test]
+    fn cyclic_embeds_become_links() {
+        let notes = [("A.md", "a\n![[B]]\n"), ("B.md", "b\n![[A]]\n")];
+        let (root, index) = vault("cyclic-embed", &notes);
+        let settings = ContentSettings::default();
+        let warnings = RefCell::new(Vec::new());
+        let context = ConversionContext::new(&index, &settings, Path::new("A.md"), Path::new("A.md"), &warnings);
+
+        let converted = convert_markdown(notes[0].1, &context);
+        fs::remove_dir_all(root).unwrap();
+
+        assert!(converted.contains("b\n") && converted.contains("[A](A.md)"), "{converted}");
+        let warnings = warnings.borrow();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("cyclic embed of A.md"), "{}", warnings[0].message);
+    }
+
+    #[test]
+    fn deeply_nested_embeds_become_links() {
+        let notes: Vec<(String, String)> = (0..=MAX_EMBED_DEPTH + 1)
+            .map(|level| (format!("N{level}.md"), format!("level {level}\n![[N{}]]\n", level + 1)))
+            .collect();
+        let notes: Vec<(&str, &str)> = notes.iter().map(|(path, content)| (path.as_str(), content.as_str())).collect();
+        let (root, index) = vault("nested-embed", &notes);
+        let settings = ContentSettings::default();
+        let warnings = RefCell::new(Vec::new());
+        let context = ConversionContext::new(&index, &settings, Path::new("N0.md"), Path::new("N0.md"), &warnings);
+
+        let converted = convert_markdown(notes[0].1, &context);
+        fs::remove_dir_all(root).unwrap();
+
+        let deepest = format!("level {MAX_EMBED_DEPTH}\n");
+        let too_deep = format!("[N{0}](N{0}.md)", MAX_EMBED_DEPTH + 1);
+        assert!(converted.contains(&deepest) && converted.contains(&too_deep), "{converted}");
+        let warnings = warnings.borrow();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("nested deeper than"), "{}", warnings[0].message);
+    }
+
+    #[test]
+    fn strips_frontmatter_only_at_the_start() {
+        assert_eq!(strip_frontmatter("---\ntags: [public]\n---\n# Note\n"), "# Note\n");
+        assert_eq!(strip_frontmatter("---\r\npublish: true\r\n---\r\ntext"), "text");
+        assert_eq!(strip_frontmatter("# Note\n---\nnot: properties\n---\n"), "# Note\n---\nnot: properties\n---\n");
+        // unterminated frontmatter is kept as it is
+        assert_eq!(strip_frontmatter("---\ntitle: x\n"), "---\ntitle: x\n");
+    }
+}
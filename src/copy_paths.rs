@@ -0,0 +1,116 @@
+//! contains logic to read paths from the `copy_paths` config section
+//!
+//! allows the config note to carry the whole publishing setup:
+//!
+//! ```markdown
+//! conf-start:copy_paths
+//! - vault: ..
+//! - dest: ../../book/src
+//! - summary: ../../book/src/SUMMARY.md
+//! - 100-199_university/lectures: lectures
+//! conf-end:
+//! ```
+//!
+//! `vault`, `dest` and `summary` are resolved against the directory of the file declaring them.
+//! every other entry maps a vault-relative directory to a directory inside of the destination,
+//! the longest matching source wins.
+//! paths given as arguments or in `[paths]` of the project file take precedence
+
+// internal imports
+use crate::slugify::{slugify_path, SlugStrategy};
+use crate::structures::{Config, ConfigType};
+
+// external imports
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+pub const VAULT_KEY: &str = "vault";
+pub const DEST_KEY: &str = "dest";
+pub const SUMMARY_KEY: &str = "summary";
+
+/// relocates a vault-relative directory inside of the destination
+#[derive(Clone)]
+pub struct PathMapping {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
+
+/// paths declared in `copy_paths`, unset ones are requested or taken from elsewhere
+#[derive(Default)]
+pub struct CopyPaths {
+    pub vault: Option<PathBuf>,
+    pub dest: Option<PathBuf>,
+    pub summary: Option<PathBuf>,
+    pub mappings: Vec<PathMapping>,
+}
+
+impl CopyPaths {
+    /// collects every `copy_paths` section, relative paths are resolved against base_dir
+    /// later entries overwrite earlier ones
+    pub fn from_configs(configurations: &[Config], base_dir: &Path) -> Result<CopyPaths, Box<dyn Error>> {
+        let mut paths = CopyPaths::default();
+        let entries = configurations
+            .iter()
+            .filter(|config| matches!(config.conf_type, ConfigType::CollectedPaths))
+            .flat_map(|config| &config.collection_of_options);
+
+        for entry in entries {
+            let Some((key, value)) = entry
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+            else {
+                return Err(format!("invalid copy path \"{entry}\", expected \"key: path\"").into());
+            };
+            match key {
+                VAULT_KEY => paths.vault = Some(base_dir.join(value)),
+                DEST_KEY => paths.dest = Some(base_dir.join(value)),
+                SUMMARY_KEY => paths.summary = Some(base_dir.join(value)),
+                source => paths.mappings.push(PathMapping {
+                    source: trim_separators(source),
+                    destination: trim_separators(value),
+                }),
+            }
+        }
+        Ok(paths)
+    }
+
+    /// fills every path not set yet with the one of other, mappings are appended
+    pub fn merge(mut self, other: CopyPaths) -> CopyPaths {
+        self.vault = self.vault.or(other.vault);
+        self.dest = self.dest.or(other.dest);
+        self.summary = self.summary.or(other.summary);
+        self.mappings.extend(other.mappings);
+        self
+    }
+}
+
+/// returns the destination-relative path of the given vault-relative path
+/// the part below a mapped source directory is slugified, the mapped destination is kept.
+/// `is_file` tells whether the last component has an extension to keep
+///
+/// EXAMPLE:
+/// with `100-199_university/lectures: lectures`
+/// "100-199_university/lectures/Week 1.md" --> "lectures/Week 1.md"
+pub fn map_destination(mappings: &[PathMapping], relative_path: &Path, strategy: SlugStrategy, is_file: bool) -> PathBuf {
+    let mapping = mappings
+        .iter()
+        .filter(|mapping| relative_path.starts_with(&mapping.source))
+        .max_by_key(|mapping| mapping.source.components().count());
+    match mapping {
+        Some(mapping) => {
+            let rest = relative_path.strip_prefix(&mapping.source).unwrap_or(relative_path);
+            mapping.destination.join(slugify_path(rest, strategy, is_file))
+        }
+        None => slugify_path(relative_path, strategy, is_file),
+    }
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// "/notes/lectures/" --> "notes/lectures"
+fn trim_separators(path: &str) -> PathBuf {
+    PathBuf::from(path.trim_matches(['/', '\\']))
+}
@@ -0,0 +1,185 @@
+//! contains the error type of the tool and the collection of errors of a single run
+//!
+//! errors of single files or directories do not stop a run, they are collected
+//! and listed in a final report. with `--strict` the first error stops the run instead.
+//! warnings (e.g. links to notes that are not published) are listed as well,
+//! but only affect the exit code with `--strict`, where they are treated as errors.
+//! the exit code denotes the kind of the most fundamental error:
+//! - 2: config (config note, project file, patterns)
+//! - 3: traversal (reading the vault)
+//! - 4: conversion (reading or converting a note)
+//! - 5: io (writing to the destination)
+
+// external imports
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ErrorKind {
+    Config,
+    Traversal,
+    Conversion,
+    Io,
+}
+
+#[derive(Debug)]
+pub struct ObsError {
+    pub kind: ErrorKind,
+    // file or directory the error originates from
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+/// every error and warning reported during a run
+#[derive(Default)]
+pub struct Diagnostics {
+    strict: bool,
+    errors: Vec<ObsError>,
+    warnings: Vec<ObsError>,
+}
+
+impl ErrorKind {
+    pub fn exit_code(self) -> ExitCode {
+        ExitCode::from(match self {
+            ErrorKind::Config => 2,
+            ErrorKind::Traversal => 3,
+            ErrorKind::Conversion => 4,
+            ErrorKind::Io => 5,
+        })
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ErrorKind::Config => "config",
+            ErrorKind::Traversal => "traversal",
+            ErrorKind::Conversion => "conversion",
+            ErrorKind::Io => "io",
+        }
+    }
+}
+
+impl ObsError {
+    pub fn new(kind: ErrorKind, message: impl fmt::Display) -> ObsError {
+        ObsError {
+            kind,
+            path: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn config(message: impl fmt::Display) -> ObsError {
+        ObsError::new(ErrorKind::Config, message)
+    }
+
+    pub fn traversal(message: impl fmt::Display) -> ObsError {
+        ObsError::new(ErrorKind::Traversal, message)
+    }
+
+    pub fn conversion(message: impl fmt::Display) -> ObsError {
+        ObsError::new(ErrorKind::Conversion, message)
+    }
+
+    pub fn io(message: impl fmt::Display) -> ObsError {
+        ObsError::new(ErrorKind::Io, message)
+    }
+
+    /// attaches the file or directory the error originates from
+    pub fn at(mut self, path: &Path) -> ObsError {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+}
+
+impl fmt::Display for ObsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "[{}] {}: {}", self.kind.label(), path.display(), self.message),
+            None => write!(f, "[{}] {}", self.kind.label(), self.message),
+        }
+    }
+}
+
+impl std::error::Error for ObsError {}
+
+impl Diagnostics {
+    pub fn new(strict: bool) -> Diagnostics {
+        Diagnostics {
+            strict,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn enable_strict(&mut self) {
+        self.strict = true;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// collects the error, in strict mode it is returned instead to stop the run
+    pub fn report(&mut self, error: ObsError) -> Result<(), ObsError> {
+        if self.strict {
+            return Err(error);
+        }
+        eprintln!("{error}");
+        self.errors.push(error);
+        Ok(())
+    }
+
+    /// collects a problem not failing the run, in strict mode it is returned as error instead
+    pub fn warn(&mut self, warning: ObsError) -> Result<(), ObsError> {
+        if self.strict {
+            return Err(warning);
+        }
+        eprintln!("warning: {warning}");
+        self.warnings.push(warning);
+        Ok(())
+    }
+
+    /// lists every collected warning and error, returns the exit code of the run
+    pub fn finish(&self) -> ExitCode {
+        if !self.warnings.is_empty() {
+            eprintln!("\nfinished with {} warning(s):", self.warnings.len());
+            for warning in &self.warnings {
+                eprintln!("  {warning}");
+            }
+        }
+        let Some(most_fundamental) = self.errors.iter().map(|error| error.kind).min() else {
+            return ExitCode::SUCCESS;
+        };
+        eprintln!("\nfinished with {} error(s):", self.errors.len());
+        for error in &self.errors {
+            eprintln!("  {error}");
+        }
+        most_fundamental.exit_code()
+    }
+
+    /// lists every collected error followed by the one stopping the run
+    pub fn abort(&self, error: ObsError) -> ExitCode {
+        self.finish();
+        eprintln!("\naborted: {error}");
+        error.kind.exit_code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warnings_only_fail_strict_runs() {
+        let mut diagnostics = Diagnostics::new(false);
+        diagnostics.warn(ObsError::conversion("could not resolve link [[missing]]")).unwrap();
+        assert_eq!(diagnostics.finish(), ExitCode::SUCCESS);
+
+        diagnostics.report(ObsError::io("could not write")).unwrap();
+        assert_eq!(diagnostics.finish(), ExitCode::from(5));
+
+        let mut strict = Diagnostics::new(true);
+        let error = strict.warn(ObsError::conversion("could not resolve link [[missing]]")).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Conversion);
+    }
+}
@@ -0,0 +1,108 @@
+//! contains logic to preview the outcome of a run without writing anything
+//!
+//! `--dry-run` prints the diff of SUMMARY.md against the existing file
+//! followed by every file to create, overwrite and delete.
+//! `--dry-run=json` prints the same plan as json instead, in a single line.
+//! progress messages are printed to stderr, thus the json is the only output on stdout
+
+// internal imports
+use crate::sync::{Action, SyncPlan};
+
+// external imports
+use clap::ValueEnum;
+use serde::Serialize;
+use similar::TextDiff;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DIFF_CONTEXT: usize = 2;
+
+#[derive(ValueEnum, Default, Clone, Copy, PartialEq)]
+pub enum PlanFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// everything a run would change
+#[derive(Serialize, Default)]
+pub struct DryRunReport {
+    // None if SUMMARY.md is not created by the command
+    pub summary: Option<SummaryChange>,
+    pub create: Vec<PathBuf>,
+    pub overwrite: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+    pub delete: Vec<PathBuf>,
+}
+
+#[derive(Serialize)]
+pub struct SummaryChange {
+    pub path: PathBuf,
+    pub exists: bool,
+    pub changed: bool,
+    // unified diff against the existing file
+    pub diff: String,
+}
+
+impl DryRunReport {
+    /// compares the planned SUMMARY.md with the existing one
+    pub fn add_summary(&mut self, path: &Path, planned: &str) {
+        let existing = fs::read_to_string(path).ok();
+        let current = existing.as_deref().unwrap_or("");
+        let diff = TextDiff::from_lines(current, planned)
+            .unified_diff()
+            .context_radius(DIFF_CONTEXT)
+            .header(&path.display().to_string(), "planned")
+            .to_string();
+        self.summary = Some(SummaryChange {
+            path: path.to_path_buf(),
+            exists: existing.is_some(),
+            changed: current != planned,
+            diff,
+        });
+    }
+
+    /// sorts the planned files by the action they would cause
+    pub fn add_plan(&mut self, plan: &SyncPlan) {
+        for (file, action) in &plan.files {
+            let target = match action {
+                Action::Create => &mut self.create,
+                Action::Overwrite => &mut self.overwrite,
+                Action::Unchanged => &mut self.unchanged,
+            };
+            target.push(file.relative_dest_path.clone());
+        }
+        self.delete.extend(plan.deletions.iter().cloned());
+    }
+
+    pub fn print(&self, format: PlanFormat) -> Result<(), Box<dyn Error>> {
+        match format {
+            PlanFormat::Json => println!("{}", serde_json::to_string(self)?),
+            PlanFormat::Text => self.print_text(),
+        }
+        Ok(())
+    }
+
+    fn print_text(&self) {
+        println!("dry run, nothing was written");
+        if let Some(summary) = &self.summary {
+            match summary.changed {
+                true => print!("{}", summary.diff),
+                false => println!("{} is up to date", summary.path.display()),
+            }
+        }
+        for (marker, files) in [("+", &self.create), ("~", &self.overwrite), ("-", &self.delete)] {
+            for file in files {
+                println!("{marker} {}", file.display());
+            }
+        }
+        println!(
+            "would add: {}, update: {}, remove: {}, keep: {}",
+            self.create.len(),
+            self.overwrite.len(),
+            self.delete.len(),
+            self.unchanged.len()
+        );
+    }
+}
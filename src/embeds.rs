@@ -0,0 +1,211 @@
+//! contains logic to convert obsidian embeds (`![[...]]`) for mdbook
+//!
+//! supported notations:
+//! - `![[image.png]]` --> `![image.png](relative/path/image.png)`
+//! - `![[image.png|300]]` / `![[image.png|300x200]]` --> `<img>` with width (and height)
+//! - `![[image.png|caption]]` --> image using caption as alt text
+//! - `![[other note]]` --> content of the note, transcluded inline
+//! - `![[other note#Section]]` --> only the given section of the note
+//! - `![[other note#^block-id]]` --> only the given block of the note
+//! - `![[file.pdf]]` --> `<embed>` tag or link, see `PdfEmbed`
+//!
+//! transcluded notes are converted recursively, cycles are detected and
+//! replaced by a plain link to the note
+
+// internal imports
+use crate::content::{convert_markdown, embed_key, strip_frontmatter, ConversionContext, PdfEmbed};
+use crate::link_resolver::{heading_to_anchor, relative_link, IndexedFile};
+use crate::structures::{string_to_fileextension, FileExtension};
+use crate::wikilinks::{display_text, WikiLink};
+
+// external imports
+use std::fs;
+use std::path::Path;
+
+/// transclusions nested deeper than this are replaced by a link
+pub const MAX_EMBED_DEPTH: usize = 8;
+
+/// converts a single embed to its mdbook representation
+pub fn convert_embed(link: &WikiLink, context: &ConversionContext) -> String {
+    // embedding a section of the same note
+    let resolved = match link.target.is_empty() {
+        true => context.index.get(context.source),
+        false => context.index.resolve(link.target, context.source),
+    };
+    let Some(resolved) = resolved else {
+        context.warn(format!("could not resolve embed ![[{}]]", link.target));
+        return display_text(link);
+    };
+
+    let destination = relative_link(context.output, &resolved.relative_dest_path);
+    let extension = resolved
+        .relative_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+
+    match string_to_fileextension(extension) {
+        FileExtension::Markdown => transclude_note(link, resolved, context),
+        FileExtension::Image => embed_image(link, &destination),
+        FileExtension::Pdf => embed_pdf(link, &destination, context.settings.pdf_embed),
+        FileExtension::Html | FileExtension::Unknown => {
+            format!("[{}]({destination})", display_text(link))
+        }
+    }
+}
+
+/// images may carry a size ("300" or "300x200") or a caption after the "|"
+fn embed_image(link: &WikiLink, destination: &str) -> String {
+    let file_name = Path::new(link.target)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(link.target);
+
+    match link.alias.and_then(parse_size) {
+        Some((width, Some(height))) => {
+            format!("<img src=\"{destination}\" alt=\"{file_name}\" width=\"{width}\" height=\"{height}\">")
+        }
+        Some((width, None)) => {
+            format!("<img src=\"{destination}\" alt=\"{file_name}\" width=\"{width}\">")
+        }
+        None => format!("![{}]({destination})", link.alias.unwrap_or(file_name)),
+    }
+}
+
+/// parses "300" or "300x200" as used by obsidian for image sizes
+fn parse_size(value: &str) -> Option<(u32, Option<u32>)> {
+    match value.split_once('x') {
+        Some((width, height)) => Some((width.trim().parse().ok()?, Some(height.trim().parse().ok()?))),
+        None => Some((value.trim().parse().ok()?, None)),
+    }
+}
+
+/// pdfs may carry a page ("#page=3") which is passed on to the viewer
+fn embed_pdf(link: &WikiLink, destination: &str, mode: PdfEmbed) -> String {
+    let fragment = link
+        .heading
+        .map(|heading| format!("#{heading}"))
+        .unwrap_or_default();
+    match mode {
+        PdfEmbed::Embed => format!(
+            "<embed src=\"{destination}{fragment}\" type=\"application/pdf\" width=\"100%\" height=\"600px\">"
+        ),
+        PdfEmbed::Link => format!("[{}]({destination}{fragment})", display_text(link)),
+    }
+}
+
+/// inserts the converted content of the embedded note
+/// falls back to a link on cycles, unreadable notes or missing sections
+fn transclude_note(link: &WikiLink, resolved: &IndexedFile, context: &ConversionContext) -> String {
+    let destination = relative_link(context.output, &resolved.relative_dest_path);
+    let anchor = link
+        .heading
+        .filter(|heading| !heading.starts_with('^'))
+        .map(|heading| format!("#{}", heading_to_anchor(heading)))
+        .unwrap_or_default();
+    let fallback = format!("[{}]({destination}{anchor})", display_text(link));
+
+    let key = embed_key(&resolved.relative_path, link.heading);
+    if context.embed_chain.contains(&key) {
+        context.warn(format!("cyclic embed of {}, inserting link instead", resolved.relative_path.display()));
+        return fallback;
+    }
+    if context.embed_chain.len() > MAX_EMBED_DEPTH {
+        context.warn(format!(
+            "embeds are nested deeper than {MAX_EMBED_DEPTH} levels, inserting link to {} instead",
+            resolved.relative_path.display()
+        ));
+        return fallback;
+    }
+
+    let content = match fs::read_to_string(&resolved.original_path) {
+        Ok(content) => content,
+        Err(error) => {
+            context.warn(format!("could not read embedded note {}\n {error}", resolved.original_path.display()));
+            return fallback;
+        }
+    };
+    let body = strip_frontmatter(&content);
+    let excerpt = match link.heading {
+        Some(heading) => match heading.strip_prefix('^') {
+            Some(block_id) => extract_block(body, block_id),
+            None => extract_section(body, heading),
+        },
+        None => Some(body.to_string()),
+    };
+    let Some(excerpt) = excerpt else {
+        context.warn(format!(
+            "could not find section #{} of embedded note {}",
+            link.heading.unwrap_or(""),
+            resolved.relative_path.display()
+        ));
+        return fallback;
+    };
+
+    let mut embed_chain = context.embed_chain.clone();
+    embed_chain.push(key);
+    let nested_context = ConversionContext {
+        index: context.index,
+        settings: context.settings,
+        source: &resolved.relative_path,
+        output: context.output,
+        embed_chain,
+        warnings: context.warnings,
+    };
+    let converted = convert_markdown(&excerpt, &nested_context);
+
+    // blank lines are required for mdbook to render markdown inside html blocks
+    format!(
+        "\n<div class=\"obsidian-embed\">\n\n{}\n\n</div>\n",
+        converted.trim_end()
+    )
+}
+
+/// returns the section starting at the given heading
+/// up to the next heading of the same or a higher level
+fn extract_section(content: &str, heading: &str) -> Option<String> {
+    let wanted = heading_to_anchor(heading);
+    let mut section = String::new();
+    let mut level: Option<usize> = None;
+
+    for line in content.lines() {
+        let heading_level = line.chars().take_while(|ch| *ch == '#').count();
+        let is_heading = heading_level > 0 && line[heading_level..].starts_with(' ');
+        match level {
+            None => {
+                if is_heading && heading_to_anchor(&line[heading_level..]) == wanted {
+                    level = Some(heading_level);
+                    section.push_str(line);
+                    section.push('\n');
+                }
+            }
+            Some(start_level) => {
+                if is_heading && heading_level <= start_level {
+                    break;
+                }
+                section.push_str(line);
+                section.push('\n');
+            }
+        }
+    }
+    level.map(|_| section)
+}
+
+/// returns the paragraph (or list item) marked with "^block-id"
+fn extract_block(content: &str, block_id: &str) -> Option<String> {
+    let marker = format!("^{block_id}");
+    let paragraphs: Vec<&str> = content.split("\n\n").collect();
+    for paragraph in paragraphs {
+        for line in paragraph.lines() {
+            if line.trim_end().ends_with(&marker) {
+                // list items are marked on their own line, paragraphs on their last line
+                let block = match line.trim_start().starts_with(['-', '*']) {
+                    true => line,
+                    false => paragraph,
+                };
+                return Some(block.replace(&format!(" {marker}"), "").replace(&marker, ""));
+            }
+        }
+    }
+    None
+}
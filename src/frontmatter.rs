@@ -0,0 +1,218 @@
+//! contains a minimal parser for the yaml frontmatter (properties) of notes
+//!
+//! the frontmatter has to start at the first line of a note and is enclosed by "---".
+//! supported is the subset written by obsidian's property editor:
+//! - scalars: `publish: true`, `title: "Some Title"`
+//! - inline lists: `tags: [public, course]`
+//! - block lists:
+//!   ```yaml
+//!   tags:
+//!     - public
+//!     - course
+//!   ```
+//!
+//! nested mappings are skipped
+
+// external imports
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+#[derive(Clone, PartialEq)]
+pub enum PropertyValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+/// properties of a single note, empty if the note has no frontmatter
+#[derive(Default, Clone, PartialEq)]
+pub struct Frontmatter {
+    properties: BTreeMap<String, PropertyValue>,
+}
+
+impl Frontmatter {
+    /// names of every property, in alphabetical order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.properties.keys().map(String::as_str)
+    }
+
+    /// returns the property as text, lists are not converted
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        match self.properties.get(key) {
+            Some(PropertyValue::Text(value)) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// whether the property is set to a non-empty value
+    /// obsidian keeps properties whose value was removed
+    pub fn has(&self, key: &str) -> bool {
+        !self.get_list(key).is_empty()
+    }
+
+    /// returns the property as boolean, accepting "true"/"false" and "yes"/"no"
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get_text(key)?.to_lowercase().as_str() {
+            "true" | "yes" => Some(true),
+            "false" | "no" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// returns the property as list, a single text value becomes a list of one
+    pub fn get_list(&self, key: &str) -> Vec<&str> {
+        match self.properties.get(key) {
+            Some(PropertyValue::List(values)) => values.iter().map(String::as_str).collect(),
+            Some(PropertyValue::Text(value)) if !value.is_empty() => vec![value.as_str()],
+            _ => Vec::new(),
+        }
+    }
+
+    /// returns every tag of the note without leading "#", in lowercase
+    /// "tag" is accepted as alias of "tags", like obsidian does
+    pub fn tags(&self) -> Vec<String> {
+        ["tags", "tag"]
+            .iter()
+            .flat_map(|key| self.get_list(key))
+            .flat_map(|value| value.split([',', ' ']))
+            .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+}
+
+// ---
+// CORE FUNCTIONS
+// ---
+
+/// reads only the frontmatter of the given note, the rest of the file is not read
+pub fn read_frontmatter(path: &Path) -> Result<Frontmatter, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = reader.lines();
+
+    match lines.next().transpose()? {
+        Some(first) if first.trim_end() == "---" => {}
+        _ => return Ok(Frontmatter::default()),
+    }
+    let mut frontmatter_lines = Vec::new();
+    for line in lines {
+        let line = line?;
+        if line.trim_end() == "---" {
+            return Ok(parse_frontmatter_lines(&frontmatter_lines));
+        }
+        frontmatter_lines.push(line);
+    }
+    // no closing "---", thus no frontmatter
+    Ok(Frontmatter::default())
+}
+
+/// parses the lines between the enclosing "---"
+pub fn parse_frontmatter_lines(lines: &[String]) -> Frontmatter {
+    let mut properties = BTreeMap::new();
+    let mut current_list: Option<(String, Vec<String>)> = None;
+
+    for line in lines {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let is_indented = line.starts_with([' ', '\t']);
+        let trimmed = line.trim();
+
+        // items of a block list belong to the last key without value
+        if let Some(item) = trimmed.strip_prefix("- ").or((trimmed == "-").then_some("")) {
+            if let Some((_, items)) = current_list.as_mut() {
+                let item = unquote(item);
+                if !item.is_empty() {
+                    items.push(item);
+                }
+            }
+            continue;
+        }
+        if is_indented {
+            // nested mappings are not supported
+            continue;
+        }
+        if let Some((key, items)) = current_list.take() {
+            properties.insert(key, PropertyValue::List(items));
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = unquote(key);
+        let value = value.trim();
+        if value.is_empty() {
+            current_list = Some((key, Vec::new()));
+        } else if let Some(inline) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            let items = inline
+                .split(',')
+                .map(unquote)
+                .filter(|item| !item.is_empty())
+                .collect();
+            properties.insert(key, PropertyValue::List(items));
+        } else {
+            properties.insert(key, PropertyValue::Text(unquote(value)));
+        }
+    }
+    if let Some((key, items)) = current_list {
+        properties.insert(key, PropertyValue::List(items));
+    }
+    Frontmatter { properties }
+}
+
+/// removes surrounding whitespace and quotes
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Frontmatter {
+        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+        parse_frontmatter_lines(&lines)
+    }
+
+    #[test]
+    fn parses_scalars_and_quotes() {
+        let frontmatter = parse("title: \"Some: Title\"\npublish: yes\ndraft: false\nweight: '3'\n# comment: ignored");
+        assert_eq!(frontmatter.get_text("title"), Some("Some: Title"));
+        assert_eq!(frontmatter.get_bool("publish"), Some(true));
+        assert_eq!(frontmatter.get_bool("draft"), Some(false));
+        assert_eq!(frontmatter.get_text("weight"), Some("3"));
+        assert_eq!(frontmatter.get_bool("title"), None);
+        assert!(!frontmatter.has("comment"));
+    }
+
+    #[test]
+    fn parses_inline_and_block_lists() {
+        let frontmatter = parse("tags: [public, \"course\"]\naliases:\n  - first\n  - 'second'\n  -\nempty:\n");
+        assert_eq!(frontmatter.get_list("tags"), vec!["public", "course"]);
+        assert_eq!(frontmatter.get_list("aliases"), vec!["first", "second"]);
+        assert!(!frontmatter.has("empty"));
+        // lists are not converted to text
+        assert_eq!(frontmatter.get_text("tags"), None);
+    }
+
+    #[test]
+    fn skips_nested_mappings() {
+        let frontmatter = parse("author:\n  name: someone\ntitle: Note");
+        assert!(!frontmatter.has("name"));
+        assert_eq!(frontmatter.get_text("title"), Some("Note"));
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        let frontmatter = parse("tags: \"#Public, course\"\ntag:\n  - Draft");
+        assert_eq!(frontmatter.tags(), vec!["public", "course", "draft"]);
+    }
+}
@@ -0,0 +1,251 @@
+//! contains logic to resolve link targets written in obsidian-notation
+//! against the collected `Directory` tree
+//!
+//! resolution follows the "shortest path when possible" behavior of obsidian:
+//! - `[[note]]` matches every file called `note.md` anywhere in the vault
+//! - `[[dir/note]]` matches every file whose path ends with `dir/note.md`
+//! - if several files match, the one closest to the linking file wins,
+//!   followed by the one with the shortest path
+//!
+//! matching is case-insensitive, just like obsidian
+
+// internal imports
+use crate::structures::{string_to_fileextension, Directory, FileExtension};
+
+// external imports
+use std::path::{Component, Path, PathBuf};
+
+/// flat index of every collected file, used to resolve links
+pub struct LinkIndex {
+    entries: Vec<IndexedFile>,
+}
+
+/// file a link may resolve to
+pub struct IndexedFile {
+    // lowercase path components relative to the vault root, including extension
+    components: Vec<String>,
+    pub relative_path: PathBuf,
+    pub relative_dest_path: PathBuf,
+    pub original_path: PathBuf,
+}
+
+impl LinkIndex {
+    /// collects every file of the given directory and its sub directories
+    pub fn from_directory(root: &Directory) -> LinkIndex {
+        let mut entries = Vec::new();
+        collect_entries(root, &mut entries);
+        LinkIndex { entries }
+    }
+
+    /// indexes files that keep their relative path in the destination
+    /// used by the preprocessor, as the book already contains every file
+    pub fn from_files(root: &Path, relative_paths: Vec<PathBuf>) -> LinkIndex {
+        let entries = relative_paths
+            .into_iter()
+            .map(|relative_path| index_entry(&relative_path, &relative_path, &root.join(&relative_path)))
+            .collect();
+        LinkIndex { entries }
+    }
+
+    /// source and destination paths of every collected file
+    /// conversion of links only depends on these
+    pub fn structure(&self) -> Vec<PathBuf> {
+        self.entries
+            .iter()
+            .flat_map(|entry| [entry.relative_path.clone(), entry.relative_dest_path.clone()])
+            .collect()
+    }
+
+    /// resolves given link target to a collected file
+    /// `source` denotes the vault-relative path of the file containing the link
+    /// returns None if no file matches
+    pub fn resolve(&self, target: &str, source: &Path) -> Option<&IndexedFile> {
+        let target = target.trim().trim_start_matches('/');
+        if target.is_empty() {
+            return None;
+        }
+        let mut wanted: Vec<String> = target
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .map(|part| part.to_lowercase())
+            .collect();
+        // links to notes usually omit the extension
+        if !has_known_extension(target) {
+            if let Some(last) = wanted.last_mut() {
+                last.push_str(".md");
+            }
+        }
+
+        let source_dir = source.parent().unwrap_or(Path::new(""));
+        self.entries
+            .iter()
+            .filter(|entry| entry.components.ends_with(&wanted))
+            .min_by_key(|entry| {
+                let in_source_dir = entry.relative_path.parent() == Some(source_dir);
+                (!in_source_dir, entry.components.len(), entry.relative_path.clone())
+            })
+    }
+
+    /// returns the collected file with the given vault-relative path
+    pub fn get(&self, relative_path: &Path) -> Option<&IndexedFile> {
+        self.entries.iter().find(|entry| entry.relative_path == relative_path)
+    }
+}
+
+/// drafts are not copied, thus links to them cannot be resolved
+fn collect_entries(directory: &Directory, entries: &mut Vec<IndexedFile>) {
+    for file in directory.files.iter().filter(|file| !file.draft) {
+        entries.push(index_entry(&file.relative_path, &file.relative_dest_path, &file.original_path));
+    }
+    for sub_directory in &directory.sub_directories {
+        collect_entries(sub_directory, entries);
+    }
+}
+
+fn index_entry(relative_path: &Path, relative_dest_path: &Path, original_path: &Path) -> IndexedFile {
+    IndexedFile {
+        components: relative_path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => part.to_str().map(str::to_lowercase),
+                _ => None,
+            })
+            .collect(),
+        relative_path: relative_path.to_path_buf(),
+        relative_dest_path: relative_dest_path.to_path_buf(),
+        original_path: original_path.to_path_buf(),
+    }
+}
+
+/// checks whether the target ends with an extension we collect files for
+/// "Note v1.2" is not mistaken for a file with extension "2"
+fn has_known_extension(target: &str) -> bool {
+    Path::new(target)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| !matches!(string_to_fileextension(&ext.to_lowercase()), FileExtension::Unknown))
+        .unwrap_or(false)
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// creates a relative link from the file `from` to the file `to`
+/// both paths are relative to the destination root
+///
+/// EXAMPLE:
+/// from: dir1/dir2/source.md
+/// to: dir1/other/target.md
+/// returns ../other/target.md
+pub fn relative_link(from: &Path, to: &Path) -> String {
+    let from_dir: Vec<Component> = from.parent().map(|p| p.components().collect()).unwrap_or_default();
+    let to_components: Vec<Component> = to.components().collect();
+
+    let common = from_dir
+        .iter()
+        .zip(to_components.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_dir.len() - common];
+    parts.extend(
+        to_components[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().to_string()),
+    );
+    encode_link(&parts.join("/"))
+}
+
+/// creates the link to a chapter as used in SUMMARY.md
+/// mdbook does not decode escaped characters there, so paths containing
+/// whitespace or parentheses are enclosed in "<>" instead
+pub fn summary_link(relative_dest_path: &Path) -> String {
+    let link = relative_dest_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if link.contains(|ch: char| ch.is_whitespace() || ch == '(' || ch == ')') {
+        return format!("<{link}>");
+    }
+    link
+}
+
+/// escapes characters that would otherwise end or break a markdown link destination
+pub fn encode_link(link: &str) -> String {
+    link.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+/// converts a heading to the anchor id generated by mdbook
+/// alphanumeric characters, "_" and "-" are kept, whitespace is replaced by "-"
+///
+/// EXAMPLE:
+/// "Some Heading: 2nd Part" --> "some-heading-2nd-part"
+pub fn heading_to_anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .chars()
+        .filter_map(|ch| {
+            if ch.is_alphanumeric() || ch == '_' || ch == '-' {
+                Some(ch.to_lowercase().collect::<String>())
+            } else if ch.is_whitespace() {
+                Some("-".to_string())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(paths: &[&str]) -> LinkIndex {
+        LinkIndex::from_files(Path::new("/vault"), paths.iter().map(PathBuf::from).collect())
+    }
+
+    fn resolve(index: &LinkIndex, target: &str, source: &str) -> Option<PathBuf> {
+        index.resolve(target, Path::new(source)).map(|file| file.relative_path.clone())
+    }
+
+    #[test]
+    fn resolves_the_closest_and_shortest_match() {
+        let index = index(&["Note.md", "a/Note.md", "a/b/Note.md", "c/Other.md"]);
+        assert_eq!(resolve(&index, "Note", "x.md"), Some(PathBuf::from("Note.md")));
+        assert_eq!(resolve(&index, "Note", "a/b/x.md"), Some(PathBuf::from("a/b/Note.md")));
+        assert_eq!(resolve(&index, "b/Note", "x.md"), Some(PathBuf::from("a/b/Note.md")));
+        assert_eq!(resolve(&index, "/c/Other", "a/x.md"), Some(PathBuf::from("c/Other.md")));
+        assert_eq!(resolve(&index, "Missing", "x.md"), None);
+        assert_eq!(resolve(&index, "", "x.md"), None);
+    }
+
+    #[test]
+    fn resolves_case_insensitive_and_with_extensions() {
+        let index = index(&["Notes/Intro.md", "images/Diagram.PNG", "Note v1.2.md"]);
+        assert_eq!(resolve(&index, "notes/intro", "x.md"), Some(PathBuf::from("Notes/Intro.md")));
+        assert_eq!(resolve(&index, "Intro.md", "x.md"), Some(PathBuf::from("Notes/Intro.md")));
+        assert_eq!(resolve(&index, "diagram.png", "x.md"), Some(PathBuf::from("images/Diagram.PNG")));
+        // "2" is no extension of a collected file
+        assert_eq!(resolve(&index, "Note v1.2", "x.md"), Some(PathBuf::from("Note v1.2.md")));
+    }
+
+    #[test]
+    fn creates_relative_and_summary_links() {
+        assert_eq!(relative_link(Path::new("dir1/dir2/source.md"), Path::new("dir1/other/target.md")), "../other/target.md");
+        assert_eq!(relative_link(Path::new("source.md"), Path::new("My Notes/a (1).md")), "My%20Notes/a%20%281%29.md");
+        assert_eq!(summary_link(Path::new("My Notes/a.md")), "<My Notes/a.md>");
+        assert_eq!(summary_link(Path::new("notes/a.md")), "notes/a.md");
+    }
+
+    #[test]
+    fn converts_headings_to_mdbook_anchors() {
+        assert_eq!(heading_to_anchor("Some Heading: 2nd Part"), "some-heading-2nd-part");
+        assert_eq!(heading_to_anchor("  snake_case & kebab-case "), "snake_case--kebab-case");
+        assert_eq!(heading_to_anchor("Übersicht"), "übersicht");
+    }
+}
@@ -2,190 +2,537 @@
 pub mod structures;
 pub mod config_parser;
 pub mod settings;
-
-use settings::{CONFIG_SOURCE, PATH_DEST, PATH_SOURCE, PATH_SUMMARY, PRINT_DEBUG, REQUEST_PATHS};
-use structures::{string_to_fileextension, CollectedPaths, Config, ConfigType, Directory, FileExtension};
-use config_parser::{parse_configuration,print_config};
+pub mod cli;
+pub mod project_file;
+pub mod link_resolver;
+pub mod wikilinks;
+pub mod embeds;
+pub mod callouts;
+pub mod slugify;
+pub mod patterns;
+pub mod frontmatter;
+pub mod publish;
+pub mod summary;
+pub mod titles;
+pub mod content;
+pub mod copy_paths;
+pub mod ordering;
+pub mod sync;
+pub mod dry_run;
+pub mod diagnostics;
+pub mod watch;
+pub mod preprocessor;
+pub mod overrides;
+
+use cli::{Cli, Command, PathArgs};
+use settings::{OutputSettings, Rules, Settings};
+use structures::{string_to_fileextension, CollectedPaths, Config, Directory, FileData, FileExtension};
+use config_parser::{load_configuration,print_config,LoadedConfiguration};
+use project_file::{find_project_file, load_project_file, ProjectFile, ProjectPaths};
+use link_resolver::LinkIndex;
+use content::{convert_markdown, ContentSettings, ConversionCache, ConversionContext};
+use callouts::write_callout_css;
+use copy_paths::{map_destination, CopyPaths};
+use ordering::sort_directory;
+use dry_run::DryRunReport;
+use diagnostics::{Diagnostics, ObsError};
+use sync::{execute_plan, plan_sync, print_plan_summary, OutputSource, PlannedFile};
+use frontmatter::{read_frontmatter, Frontmatter};
+use publish::{publish_state, PublishState};
+use summary::{create_book_summary, collect_index_pages, find_folder_note};
+use titles::resolve_title;
+use watch::watch_vault;
+use preprocessor::run_preprocessor;
+use overrides::{is_override_file, DirectoryOverrides};
 
 // external import
+use clap::Parser;
+use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::error::Error;
-use std::io::{self, BufReader, Write};
+use std::io::{self, Write};
 use std::fs;
 use std::fs::File;
 use std::path::{Path, PathBuf,};
+use std::process::ExitCode;
+
 
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if let Some(Command::Preprocessor(args)) = &cli.command {
+        return run_preprocessor(args);
+    }
+    let mut diagnostics = Diagnostics::new(cli.strict);
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Insert a given path to traverse its directory and all contained files and directories");
+    match run(cli, &mut diagnostics) {
+        Ok(_) => diagnostics.finish(),
+        Err(error) => diagnostics.abort(error),
+    }
+}
+
+/// executes the given subcommand once per selected profile
+/// errors returned stop the run, every other one is collected in diagnostics
+fn run(cli:Cli, diagnostics:&mut Diagnostics) -> Result<(), ObsError> {
+    let command = cli.command.unwrap_or(Command::Build(PathArgs::default()));
+    let project_path = cli.project.or_else(|| find_project_file(&std::env::current_dir().ok()?));
+
+    let profiles: Vec<Option<String>> = match &project_path {
+        None if cli.all_profiles || cli.profile.is_some() => {
+            return Err(ObsError::config("profiles are declared in the project file, but none was found"));
+        },
+        Some(path) if cli.all_profiles => load_project_file(path, None)
+            .map_err(|e| ObsError::config(e).at(path))?
+            .profiles
+            .into_keys()
+            .map(Some)
+            .collect(),
+        _ => vec![cli.profile],
+    };
+    if profiles.is_empty() {
+        return Err(ObsError::config("no profiles declared in the project file"));
+    }
+    if profiles.len() > 1 && matches!(command, Command::Watch(_)) {
+        return Err(ObsError::config("watch supports a single profile only, select it with --profile"));
+    }
 
-    let configurations = wrapper_parse_config()?;
+    for profile in &profiles {
+        if let Some(name) = profile {
+            eprintln!("\nbuilding profile {name}");
+        }
+        run_profile(&command, cli.debug, project_path.as_deref(), profile.as_deref(), diagnostics)?;
+    }
+    Ok(())
+}
 
-    if settings::PRINT_DEBUG{
+/// loads the configuration of the given profile, traverses the vault and executes the given subcommand
+fn run_profile(
+    command:&Command,
+    debug:bool,
+    project_path:Option<&Path>,
+    profile:Option<&str>,
+    diagnostics:&mut Diagnostics) -> Result<(), ObsError> {
+    let project = match project_path {
+        Some(path) => {
+            eprintln!("loading project file {}",path.display());
+            Some(load_project_file(path, profile).map_err(|e| ObsError::config(e).at(path))?)
+        },
+        None => None,
+    };
+    if project.as_ref().is_some_and(|project| project.strict) {
+        diagnostics.enable_strict();
+    }
+
+    let config_source = request_config_source(command, project.as_ref());
+    // lists of project file and config note are merged
+    let mut configurations: Vec<Config> = project
+        .as_ref()
+        .map(|project| project.config.to_configs())
+        .unwrap_or_default();
+    let project_dir = project_path.and_then(Path::parent).unwrap_or(Path::new(""));
+    let mut copy_paths = CopyPaths::from_configs(&configurations, project_dir)
+        .map_err(|e| ObsError::config(e).at(project_dir))?;
+    // every file the configuration is read from, watched for changes
+    let mut config_files: Vec<PathBuf> = project_path.map(Path::to_path_buf).into_iter().collect();
+    if let Some(config_source) = &config_source {
+        let config_error = |e| ObsError::config(e).at(config_source);
+        let loaded = load_config_note(config_source)?;
+        let note_dir = config_source.parent().unwrap_or(Path::new(""));
+        copy_paths = copy_paths.merge(CopyPaths::from_configs(&loaded.configs, note_dir).map_err(config_error)?);
+        configurations.extend(loaded.configs);
+        config_files.extend(loaded.notes);
+    }
+    let mut settings = request_settings(command, debug, project.as_ref(), config_source, copy_paths);
+    // rebuilds should only touch what changed
+    if let Command::Watch(_) = command {
+        settings.output.sync = true;
+        // changes are reported with canonical paths, vault and destination are compared against them
+        settings.paths.root_dir = canonical_path(&settings.paths.root_dir);
+        if !settings.paths.dest_dir.as_os_str().is_empty() {
+            settings.paths.dest_dir = canonical_path(&settings.paths.dest_dir);
+        }
+    }
+
+    if settings.print_debug{
         print_config(&configurations);  
     }
-    // filtering out configuration that handle excluded_dirs 
-    //IMPORTANT: assuming multiple may exist
-    let blacklisted_files: Vec<String> = configurations
-    .iter()
-    .filter_map(|config| match config.conf_type {
-       ConfigType::ExcludedFiles => Some(config.collection_of_options.clone()),
-       _ => None,
-    })
-    .flatten() // reducing to one vector
-    .collect();
-
-    let whitelisted_directories: Vec<String> = configurations
-    .iter()
-    .filter_map(|config| match config.conf_type {
-        ConfigType::IncludedDirectories => Some(config.collection_of_options.clone()),
-        _ => None,
-    })
-    .flatten()
-    .collect();
-
-
-    let paths: CollectedPaths = request_paths();
-    let root_path = paths.root_dir;
-    let save_path = paths.dest_file;
-    let copy_directory = paths.dest_dir;
-    if PRINT_DEBUG{
-        println!("found following paths:\nroot:{}\ndest:{}\nsummary:{}\n",root_path.display(),copy_directory.display(),save_path.display())
-    }
-
-    // display_folder(&file_path);
-    let parsed_dir = collect_dir_structure(
-        &root_path,
-        &whitelisted_directories,
-        &blacklisted_files,
-        &copy_directory,
-        &root_path,
-        );
-
-    match parsed_dir {
-        Ok(dir) => {
-            // visualize_directory(&dir,Some(1));
-            let presentation:String = create_book_summary(&dir);
-            // println!("{}",presentation);
-            match save_to_file(&save_path, presentation) {
-                Ok(_) => (),
-                Err(error) => println!("{error}")
-            }
+    let rules = Rules::from_configs(&configurations)?;
 
-            // COPYING FILES to new destination
-            println!("copying files to destination: {}",&save_path.display());
-            copy_directory_to_dest(&dir);
-            println!("done copying files, update mdbook accordingly!")
+    let root_path = &settings.paths.root_dir;
+    let save_path = &settings.paths.dest_file;
+    let copy_directory = &settings.paths.dest_dir;
+    if settings.print_debug{
+        eprintln!("found following paths:\nroot:{}\ndest:{}\nsummary:{}\n",root_path.display(),copy_directory.display(),save_path.display())
+    }
 
+    if let Command::Watch(_) = command {
+        let loaded = LoadedRules { rules, config_files };
+        return watch(command, &settings, loaded, project_path, profile, diagnostics.is_strict());
+    }
+    let dir = collect_vault(&settings, &rules, diagnostics)?;
+    run_command(command, &dir, &settings, diagnostics, None)
+}
 
-        },
-        Err(err) => println!("error {}",err)
+/// traverses the vault and sorts the collected chapters
+fn collect_vault(settings:&Settings, rules:&Rules, diagnostics:&mut Diagnostics) -> Result<Directory, ObsError> {
+    let root_path = &settings.paths.root_dir;
+    let overrides = DirectoryOverrides::load(root_path, &DirectoryOverrides::default())?;
+    if overrides.exclude {
+        return Err(ObsError::config("the root of the vault can not be excluded, remove `exclude` from its override file").at(root_path));
     }
-    Ok(())
+    let mut dir = collect_dir_structure(root_path, &overrides, rules, settings, diagnostics)?;
+    sort_directory(&mut dir, settings.output.order, &rules.order);
+    Ok(dir)
 }
 
-fn request_paths() -> CollectedPaths {
+/// rules of the configuration along with every file they are read from
+struct LoadedRules {
+    rules:Rules,
+    config_files:Vec<PathBuf>,
+}
 
-    println!("collecting");
-    if REQUEST_PATHS{
-        CollectedPaths{
-            root_dir: enforce_filepath(request_filepath),
-            dest_dir: enforce_filepath(request_copy_path),
-            dest_file: enforce_filepath(request_save_file)
+/// builds once, then rebuilds whenever the vault or one of the config files changes
+/// the vault is only traversed again if the changes may alter the collected structure,
+/// notes are only converted again if they changed. errors are reported per rebuild and do not stop watching
+fn watch(
+    command:&Command,
+    settings:&Settings,
+    mut loaded:LoadedRules,
+    project_path:Option<&Path>,
+    profile:Option<&str>,
+    strict:bool) -> Result<(), ObsError> {
+    let mut cache = ConversionCache::default();
+    // the directory is kept if the vault does not have to be traversed again
+    let rebuild = |rules:&Rules, cache:&mut ConversionCache, previous:Option<Directory>| -> Option<Directory> {
+        let mut diagnostics = Diagnostics::new(strict);
+        let collected = match previous {
+            Some(dir) => Ok(dir),
+            None => collect_vault(settings, rules, &mut diagnostics),
+        };
+        let result = collected.and_then(|dir| run_command(command, &dir, settings, &mut diagnostics, Some(cache)).map(|_| dir));
+        match result {
+            Ok(dir) => {
+                diagnostics.finish();
+                Some(dir)
+            },
+            Err(error) => {
+                diagnostics.abort(error);
+                None
+            },
         }
-    } else {
-        CollectedPaths{ 
-            root_dir: PathBuf::from(PATH_SOURCE),
-            dest_dir: PathBuf::from(PATH_DEST),
-            dest_file: PathBuf::from(PATH_SUMMARY)
+    };
+    let mut collected = rebuild(&loaded.rules, &mut cache, None);
+
+    let config_files = loaded.config_files.clone();
+    watch_vault(&settings.paths.root_dir, &config_files, |changes| {
+        if changes.config_changed {
+            println!("config changed, reloading");
+            match reload_rules(project_path, profile, settings.config_source.as_deref()) {
+                Ok(reloaded) => loaded = reloaded,
+                Err(error) => {
+                    println!("keeping previous config\n {error}");
+                    return None;
+                },
+            }
+            cache.clear();
+            collected = rebuild(&loaded.rules, &mut cache, None);
+            return Some(loaded.config_files.clone());
+        }
+        let relevant: Vec<&PathBuf> = changes.paths.iter().filter(|path| is_relevant(path, &loaded.rules, settings)).collect();
+        if relevant.is_empty() {
+            return None;
         }
+        let previous = collected
+            .take()
+            .filter(|dir| relevant.iter().all(|path| keeps_structure(dir, path, &loaded.rules, settings)));
+        collected = rebuild(&loaded.rules, &mut cache, previous);
+        None
+    })
+}
+
+/// reads the project file and the config note again, paths and settings are kept until restarting
+fn reload_rules(project_path:Option<&Path>, profile:Option<&str>, config_source:Option<&Path>) -> Result<LoadedRules, ObsError> {
+    let mut configurations = match project_path {
+        Some(path) => load_project_file(path, profile).map_err(|e| ObsError::config(e).at(path))?.config.to_configs(),
+        None => Vec::new(),
+    };
+    let mut config_files: Vec<PathBuf> = project_path.map(Path::to_path_buf).into_iter().collect();
+    if let Some(path) = config_source {
+        let loaded = load_config_note(path)?;
+        configurations.extend(loaded.configs);
+        config_files.extend(loaded.notes);
     }
+    let rules = Rules::from_configs(&configurations)?;
+    Ok(LoadedRules { rules, config_files })
+}
 
+/// whether a changed file leaves the collected structure as it is:
+/// it was collected before, still exists and neither its properties nor its title changed.
+/// new, removed or renamed files and override files always require a traversal
+fn keeps_structure(dir:&Directory, path:&Path, rules:&Rules, settings:&Settings) -> bool {
+    let Some(file) = dir.find_file(path) else {
+        return false;
+    };
+    if !path.is_file() {
+        return false;
+    }
+    match file.extension {
+        FileExtension::Markdown => match read_frontmatter(path) {
+            Ok(frontmatter) => {
+                let title = note_title(path, &file.relative_path, &file.name, &frontmatter, rules, settings);
+                frontmatter == file.frontmatter && title == file.title
+            },
+            Err(_) => false,
+        },
+        _ => true,
+    }
 }
 
-/// takes directory and copies it - recursively - to new destination
-fn copy_directory_to_dest(base_dir:&Directory) -> () {
+/// absolute path without symlinks, paths not existing yet are only made absolute
+fn canonical_path(path:&Path) -> PathBuf {
+    path.canonicalize()
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or(path.to_path_buf())
+}
 
-    // create directory first 
-    let dest_dir = &base_dir.dest_path;
-    if !dest_dir.exists() { 
-        // does not exist, creating 
-        // FIXME improved error handling
-        let result_creation = fs::create_dir_all(dest_dir);
+/// whether a changed path may affect the book:
+/// it lies inside of the vault, outside of the destination and is not excluded by the config
+fn is_relevant(path:&Path, rules:&Rules, settings:&Settings) -> bool {
+    let dest_dir = &settings.paths.dest_dir;
+    if !dest_dir.as_os_str().is_empty() && path.starts_with(dest_dir) {
+        return false;
     }
-    // copying files over from current directory
-    for file in &base_dir.files{
-        let file_copy_result = fs::copy(&file.original_path, &file.dest_path);
+    let Ok(relative) = path.strip_prefix(&settings.paths.root_dir) else {
+        return false;
+    };
+    let parents_included = relative
+        .ancestors()
+        .skip(1)
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .all(|parent| rules.filters.includes_directory(parent));
+    let included = match path.is_dir() {
+        true => rules.filters.includes_directory(relative),
+        false => rules.filters.includes_file(relative),
+    };
+    parents_included && included
+}
+
+/// executes the given subcommand on the already collected directory
+fn run_command(
+    command:&Command,
+    dir:&Directory,
+    settings:&Settings,
+    diagnostics:&mut Diagnostics,
+    mut cache:Option<&mut ConversionCache>) -> Result<(), ObsError> {
+    let save_path = &settings.paths.dest_file;
+    let mut report = DryRunReport::default();
+
+    if command.requires_summary() {
+        let presentation:String = create_book_summary(dir, &settings.output);
+        if settings.dry_run.is_some() {
+            report.add_summary(save_path, &presentation);
+        } else if fs::read_to_string(save_path).ok().as_deref() != Some(presentation.as_str()) {
+            // unchanged summaries are not written, as mdbook rebuilds on every write
+            if let Err(error) = save_to_file(save_path, presentation) {
+                diagnostics.report(ObsError::io(format!("could not save summary\n {error}")).at(save_path))?;
+            }
+        }
     }
 
-    // once all have been copied, traverse to next directory 
-    for directory in &base_dir.sub_directories{
-        copy_directory_to_dest(&directory);
+    if command.requires_dest() {
+        // COPYING FILES to new destination
+        let index = LinkIndex::from_directory(dir);
+        if let Some(cache) = cache.as_deref_mut() {
+            cache.validate(index.structure());
+        }
+        let mut outputs = Vec::new();
+        collect_outputs(dir, &index, &settings.content, &mut outputs, diagnostics, cache)?;
+        collect_index_pages(dir, &settings.output, &mut outputs);
+        let plan = plan_sync(outputs, &settings.paths.dest_dir, settings.output.sync, diagnostics)?;
+        if settings.dry_run.is_some() {
+            report.add_plan(&plan);
+        } else {
+            println!("copying files to destination: {}",settings.paths.dest_dir.display());
+            execute_plan(&plan, diagnostics)?;
+            print_plan_summary(&plan);
+            if settings.content.callout_css {
+                match write_callout_css(&settings.paths.dest_dir) {
+                    Ok(css_path) => println!("created stylesheet for callouts: {}",css_path.display()),
+                    Err(error) => diagnostics.report(
+                        ObsError::io(format!("could not create stylesheet for callouts\n {error}")).at(&settings.paths.dest_dir)
+                    )?,
+                }
+            }
+            println!("done copying files, update mdbook accordingly!")
+        }
     }
-    
 
+    if let Some(format) = settings.dry_run.filter(|_| !matches!(command, Command::Check(_))) {
+        if let Err(error) = report.print(format) {
+            diagnostics.report(ObsError::io(format!("could not print planned changes\n {error}")))?;
+        }
+    }
 
+    if let Command::Check(_) = command {
+        if settings.print_debug{
+            visualize_directory(dir, Some(1));
+        }
+        println!("config and vault are valid, nothing was written");
+    }
+    Ok(())
+}
 
+/// takes the config note given as argument or in the project file
+/// it is only requested if no project file was found
+fn request_config_source(command:&Command, project:Option<&ProjectFile>) -> Option<PathBuf> {
+    let project_config = project.and_then(|project| project.paths.config.clone());
+    match command.path_args().config.clone().or(project_config) {
+        Some(path) => Some(path),
+        None if project.is_none() => {
+            println!("Insert a given path to traverse its directory and all contained files and directories");
+            Some(enforce_filepath(request_config_path))
+        },
+        None => None,
+    }
 }
 
-/// opens and converts file to vector of configurations, or returns error
-/// 
-fn wrapper_parse_config() -> Result<Vec<Config>, Box<dyn Error>>{
+/// assembles settings from the given arguments, falling back to the project file
+/// and the `copy_paths` of the configuration
+/// requests every missing path that is required by the command
+fn request_settings(
+    command:&Command,
+    print_debug:bool,
+    project:Option<&ProjectFile>,
+    config_source:Option<PathBuf>,
+    copy_paths:CopyPaths) -> Settings {
+    let args = command.path_args();
+    let default_project = ProjectFile::default();
+    let project_paths = &project.unwrap_or(&default_project).paths;
+
+    Settings{
+        config_source,
+        paths: request_paths(command, project_paths, copy_paths),
+        print_debug: print_debug || project.is_some_and(|project| project.debug),
+        dry_run: args.dry_run,
+        content: project.map(|project| project.content.clone()).unwrap_or_default(),
+        publish: project.map(|project| project.publish.clone()).unwrap_or_default(),
+        output: request_output_settings(args, project),
+    }
+}
 
-    let path:PathBuf = PathBuf::from(CONFIG_SOURCE);
-    let file_reader = read_from_file(&path)?;
-    parse_configuration(file_reader)
+/// takes output settings from the project file, arguments take precedence
+fn request_output_settings(args:&PathArgs, project:Option<&ProjectFile>) -> OutputSettings {
+    let mut output = project.map(|project| project.output.clone()).unwrap_or_default();
+    if let Some(slugify) = args.slugify {
+        output.slugify = slugify;
+    }
+    if let Some(summary_style) = args.summary_style {
+        output.summary_style = summary_style;
+    }
+    if let Some(order) = args.order {
+        output.order = order;
+    }
+    if args.sync {
+        output.sync = true;
+    }
+    output
 }
 
-/// checks whether last dir in path is included in whitelist
-/// returns false otherwise
-fn contains_included_directory(path: &Path,whitelist: &Vec<String>) -> bool {
-    if let Some(last_component) = path.components().last() {
-        if let Some(component_str) = last_component.as_os_str().to_str() {
-            if whitelist.contains(&component_str.to_string()) {
-                return true;
-            }
-        }
+/// takes paths given as arguments, in the project file or in `copy_paths`, requests the remaining ones
+/// paths that are not required by the command are left empty
+fn request_paths(command:&Command, project_paths:&ProjectPaths, copy_paths:CopyPaths) -> CollectedPaths {
+    let args = command.path_args();
+
+    CollectedPaths{
+        root_dir: args.vault.clone()
+            .or_else(|| project_paths.vault.clone())
+            .or(copy_paths.vault)
+            .unwrap_or_else(|| enforce_filepath(request_filepath)),
+        dest_dir: match args.dest.clone().or_else(|| project_paths.dest.clone()).or(copy_paths.dest) {
+            Some(path) => path,
+            None if command.requires_dest() => enforce_filepath(request_copy_path),
+            None => PathBuf::new(),
+        },
+        dest_file: match args.summary.clone().or_else(|| project_paths.summary.clone()).or(copy_paths.summary) {
+            Some(path) => path,
+            None if command.requires_summary() => enforce_filepath(request_save_file),
+            None => PathBuf::new(),
+        },
+        mappings: copy_paths.mappings,
     }
-    return false
 }
 
-fn contains_excluded_file_string(string_to_compare: &String,exclusion:&Vec<String>) -> bool {
-    for word in exclusion { 
-        if string_to_compare.contains(word){
-            return true 
+/// collects every file of the directory - recursively - to be placed in the destination
+/// markdown files are converted on the way, see content.rs
+/// files failing to convert are reported and skipped
+fn collect_outputs(
+    base_dir:&Directory,
+    index:&LinkIndex,
+    content_settings:&ContentSettings,
+    outputs:&mut Vec<PlannedFile>,
+    diagnostics:&mut Diagnostics,
+    mut cache:Option<&mut ConversionCache>) -> Result<(), ObsError> {
+    // drafts only appear in SUMMARY.md
+    for file in base_dir.files.iter().filter(|file| !file.draft){
+        match convert_file(file, index, content_settings, diagnostics, cache.as_deref_mut()) {
+            Ok(source) => outputs.push(PlannedFile{
+                relative_dest_path: file.relative_dest_path.clone(),
+                source,
+                origin: file.original_path.clone(),
+            }),
+            Err(error) => diagnostics.report(error)?,
         }
     }
-    return false
-}
 
-/// takes Directory checks whether any .md file is contained in top-level folder 
-/// returns True if one was found 
-/// false otherwise
-fn contains_md_file(directory:&Directory) -> bool { 
-    for file in &directory.files{
-        let extension  = &file.extension;
-        match extension{
-            FileExtension::Markdown=> return true,
-            _ => {}
-        };
+    for directory in &base_dir.sub_directories{
+        collect_outputs(directory, index, content_settings, outputs, diagnostics, cache.as_deref_mut())?;
     }
-    return false;
+    Ok(())
+}
 
+/// converts obsidian-syntax of markdown files, every other file is copied as it is
+/// notes found in the cache are not converted again
+fn convert_file(
+    file:&FileData,
+    index:&LinkIndex,
+    content_settings:&ContentSettings,
+    diagnostics:&mut Diagnostics,
+    cache:Option<&mut ConversionCache>) -> Result<OutputSource, ObsError> {
+    match file.extension {
+        FileExtension::Markdown => {
+            if let Some(converted) = cache.as_ref().and_then(|cache| cache.get(&file.original_path)) {
+                return Ok(OutputSource::Content(converted.to_string()));
+            }
+            let content = fs::read_to_string(&file.original_path)
+                .map_err(|e| ObsError::conversion(format!("could not read note\n {e}")).at(&file.original_path))?;
+            let warnings = RefCell::new(Vec::new());
+            let context = ConversionContext::new(index, content_settings, &file.relative_path, &file.relative_dest_path, &warnings);
+            let converted = convert_markdown(&content, &context);
+            let warnings = warnings.into_inner();
+            // notes with warnings are converted again, so that every run reports them
+            if let Some(cache) = cache.filter(|_| warnings.is_empty()) {
+                cache.insert(&file.original_path, &content, &converted);
+            }
+            for warning in warnings {
+                diagnostics.warn(warning)?;
+            }
+            Ok(OutputSource::Content(converted))
+        },
+        _ => Ok(OutputSource::Copy(file.original_path.clone())),
+    }
+}
 
+/// parses the config note and the notes it includes, errors denote the note as their origin
+fn load_config_note(path:&Path) -> Result<LoadedConfiguration, ObsError> {
+    load_configuration(path).map_err(|e| ObsError::config(e).at(path))
 }
 
 ///cuts path up to root of path traversed 
 /// EXAMPLE:
 /// /home/user/root_dir/dir1/dir2/test.md --> /dir1/dir2/test.md
-fn remove_path_prefix(path:&PathBuf,old_path:&PathBuf) -> Result<PathBuf, Box<dyn Error>>{
-    if path.starts_with(old_path){
-        let shortened_path =  path.strip_prefix(old_path)
-        .and_then(|new_path| Ok(new_path.to_path_buf() ) )?;
-        Ok(shortened_path)
-    } else {
-       return  Err("prefix could not be removed from path: \n{path.to_display()}".into());
-    }
+fn remove_path_prefix(path:&Path,old_path:&Path) -> Result<PathBuf, ObsError>{
+    path.strip_prefix(old_path)
+        .map(Path::to_path_buf)
+        .map_err(|_| ObsError::traversal(format!("prefix {} could not be removed from path", old_path.display())).at(path))
 }
 
 /// @param
@@ -196,7 +543,7 @@ fn remove_path_prefix(path:&PathBuf,old_path:&PathBuf) -> Result<PathBuf, Box<dy
 /// dest_root_path: /home/user/target_dir
 /// trimmed_base_path: /subdir1/subdir2/target.md
 /// returns /home/user/target_dir/subdir1/subdir2/target.md
-fn create_dest_path(trimmed_base_path:&PathBuf,dest_root_path:&PathBuf) -> PathBuf{
+fn create_dest_path(trimmed_base_path:&Path,dest_root_path:&Path) -> PathBuf{
     dest_root_path.join(trimmed_base_path)
 }
 
@@ -204,44 +551,76 @@ fn create_dest_path(trimmed_base_path:&PathBuf,dest_root_path:&PathBuf) -> PathB
 /// FIXME reduce complexity, refactor to collection of functions
 fn collect_dir_structure(
     base_directory:&PathBuf,
-    whitelisted_directories:&Vec<String>,
-    blacklisted_files:&Vec<String>,
-    dest_path:&PathBuf,
-    root_path:&PathBuf) -> Result<structures::Directory,Box<dyn std::error::Error>> {  
+    overrides:&DirectoryOverrides,
+    rules:&Rules,
+    settings:&Settings,
+    diagnostics:&mut Diagnostics) -> Result<structures::Directory,ObsError> {  
+    let root_path = &settings.paths.root_dir;
+    let dest_path = &settings.paths.dest_dir;
+    let mappings = &settings.paths.mappings;
+    let filters = &rules.filters;
+    let headline_rules = &rules.headlines;
+    let output_settings = &settings.output;
+    let publish_settings = &settings.publish;
     // traversing the given Directory extracting information per subdir
     // assumes a correct path provided
     let parsed_path = Path::new(&base_directory).to_path_buf();
 
-    let trimmed_dir_path = remove_path_prefix(&parsed_path.to_path_buf(), &root_path)?;
-    let destination_path =create_dest_path(&trimmed_dir_path, &dest_path);
+    let trimmed_dir_path = remove_path_prefix(&parsed_path, root_path)?;
+    let slugified_dir_path = map_destination(mappings, &trimmed_dir_path, output_settings.slugify, false);
+    let destination_path =create_dest_path(&slugified_dir_path, dest_path);
     // initializing object for given directory
-    let mut current_dir: structures::Directory = structures::Directory{
-        name:base_directory.file_name()
+    let name = base_directory.file_name()
         .and_then(OsStr::to_str)
         .unwrap_or("")
-        .to_string(),
+        .to_string();
+    let mut current_dir: structures::Directory = structures::Directory{
+        title: headline_rules.directory_title(&trimmed_dir_path, &name, output_settings.strip_number_prefixes, overrides),
+        name,
         path: base_directory.clone(),
         dest_path: destination_path,
         relative_path: trimmed_dir_path,
+        relative_dest_path: slugified_dir_path,
         sub_directories:Vec::new(),
-        files: Vec::new()
+        files: Vec::new(),
+        folder_note: None,
+        order: overrides.order,
     };
 
-    let dirs = fs::read_dir(parsed_path)?;
+    let dirs = fs::read_dir(&parsed_path).map_err(|e| ObsError::traversal(e).at(&parsed_path))?;
     for entry in dirs{
         // traversing each entry
-        let directory = entry?;
+        let directory = match entry {
+            Ok(directory) => directory,
+            Err(error) => {
+                diagnostics.report(ObsError::traversal(error).at(&parsed_path))?;
+                continue;
+            }
+        };
         let file_path = directory.path();
         // in case a directory is found, we add those to our structure at the end 
 
         if file_path.is_dir() {
  
-        if !contains_included_directory(&file_path.as_path(), &whitelisted_directories){
+        let dir_name = directory.file_name().to_string_lossy().to_string();
+        if !filters.includes_directory(&remove_path_prefix(&file_path, root_path)?) || !overrides.includes_child(&dir_name, true){
             continue;
         }
-           match collect_dir_structure(&file_path,whitelisted_directories,blacklisted_files,dest_path,root_path) {
+           // settings of override files are passed down the tree
+           let sub_overrides = match DirectoryOverrides::load(&file_path, overrides) {
+                Ok(sub_overrides) => sub_overrides,
+                Err(error) => {
+                    diagnostics.report(error)?;
+                    continue;
+                }
+           };
+           if sub_overrides.exclude {
+                continue;
+           }
+           // only errors stopping the traversal of the sub directory reach this point
+           match collect_dir_structure(&file_path,&sub_overrides,rules,settings,diagnostics) {
                 Ok(dir) => current_dir.sub_directories.push(dir),
-                Err(error) => println!("error while processing sub_directory, with following error \n {error}"),
+                Err(error) => diagnostics.report(error)?,
             };
         };
 
@@ -259,111 +638,83 @@ fn collect_dir_structure(
             .and_then(|name| name.to_str())
             .unwrap_or("")
             .to_owned();
-            // FIXME skip pdfs as well
-            if name.contains(" ") || contains_excluded_file_string(&name, &blacklisted_files){
-                // found whitespace in path, aborting
+            let trimmed_path = remove_path_prefix(&file_path,root_path)?;
+            if !filters.includes_file(&trimmed_path) || is_override_file(&name) || !overrides.includes_child(&name, false){
                 continue;
             }
 
-            let trimmed_path = remove_path_prefix(&file_path,&root_path)?;
-            let destination_path_file =create_dest_path( &trimmed_path,&dest_path);
+            // deciding about publishing based on properties of the note
+            let mut frontmatter = Frontmatter::default();
+            if let FileExtension::Markdown = as_file_extension {
+                match read_frontmatter(&file_path) {
+                    Ok(parsed) => frontmatter = parsed,
+                    Err(error) => diagnostics.report(
+                        ObsError::traversal(format!("could not read frontmatter\n {error}")).at(&file_path)
+                    )?,
+                }
+            }
+            // attachments carry no properties, they are published along with the notes embedding them
+            let state = match as_file_extension {
+                FileExtension::Markdown => publish_state(&frontmatter, publish_settings),
+                _ => PublishState::Published,
+            };
+            if state == PublishState::Excluded {
+                continue;
+            }
+            let title = match as_file_extension {
+                FileExtension::Markdown => note_title(&file_path, &trimmed_path, &name, &frontmatter, rules, settings),
+                _ => name.clone(),
+            };
+            let slugified_path = map_destination(mappings, &trimmed_path, output_settings.slugify, true);
+            let destination_path_file =create_dest_path( &slugified_path,dest_path);
             current_dir.files.push(structures::FileData 
                 {
-                    name: name,
+                    name,
+                    title,
                     original_path: file_path,
                     dest_path: destination_path_file,
                     relative_path: trimmed_path,
+                    relative_dest_path: slugified_path,
                     extension: as_file_extension, 
+                    frontmatter,
+                    draft: state == PublishState::Draft,
                 }
             );
         };
     }
-    return Ok(current_dir);
+    // the root directory is not listed as chapter, thus has no folder note
+    if output_settings.folder_notes && !current_dir.relative_path.as_os_str().is_empty() {
+        current_dir.folder_note = find_folder_note(&current_dir);
+    }
+    Ok(current_dir)
+}
+
+/// chapter title of a note, transformed by `prefixes_for_headlines`
+fn note_title(file_path:&Path, relative_path:&Path, name:&str, frontmatter:&Frontmatter, rules:&Rules, settings:&Settings) -> String {
+    let title = resolve_title(file_path, name, frontmatter, settings.output.strip_number_prefixes);
+    rules.headlines.file_title(relative_path, &title)
 }
 
 /// visualizes supplied directory data structure 
 /// prints each entry with files at given depth
-fn visualize_directory(given_directory:&structures::Directory,indent:Option<usize>) -> () { 
-    let indent = indent.unwrap_or(0);
+fn visualize_directory(given_directory:&structures::Directory,indent:Option<usize>) { 
+    let indent = indent.unwrap_or(1);
 
     //  return information from active directory 
-    let indentation:String = std::iter::repeat(" ").take(indent).collect();
-    print!("{}|- [[{}]] :newpath {}\n"," ".repeat(indent-1),given_directory.name,given_directory.dest_path.display());
+    let indentation:String = " ".repeat(indent);
+    println!("{}|- [[{}]] :newpath {}"," ".repeat(indent-1),given_directory.name,given_directory.dest_path.display());
 
     for file in &given_directory.files {
         //  printing each file in same directory
-        print!("{}|\n",indentation);
-        print!("{}|-{}: newpath {} \n",indentation,file.name,file.dest_path.display());
+        println!("{}|",indentation);
+        println!("{}|-{}: newpath {} ",indentation,file.name,file.dest_path.display());
     }
     for folder in &given_directory.sub_directories {
         //  print directory, increase indentation
-        visualize_directory(&folder, Some(indent+1));
+        visualize_directory(folder, Some(indent+1));
         }
 }
 
-/// converts given Directory instance to string for mdbook
-/// wrapper for extract_file_representation_from_dir
-/// uses structure for SUMMARY.md for mdbook
-fn create_book_summary(directory_data:&structures::Directory) -> String {
-
-    let directory_as_string:String = extract_file_representation_from_dir(&directory_data);
-    // print!("{directory_as_string}");
-    let basic_formatting:String = format!("# SUMMARY.MD Structure\n\n{} ",directory_as_string);
-    // for entry in directory_data.files
-    return basic_formatting;
-}
-
-/// traverses Directory instance, converts to string complying for summary of mdbooks
-/// IMPORTANT: Conceptualized as _recursive function_
-fn extract_file_representation_from_dir(active_dir:&structures::Directory) -> String {
-
-    let mut dir_as_string:String = String::new();
-
-    // traversing and processing the active directory
-    let stringified_dir: String = stringify_directory(&active_dir);
-
-    dir_as_string.push_str(&stringified_dir);
-
-    // traversing all subsequent directories
-    for directory in &active_dir.sub_directories {
-        let dir_string = extract_file_representation_from_dir(&directory);
-        dir_as_string.push_str(&dir_string);
-    }
-
-    return dir_as_string
-}
-
-/// converts a Directory to string representation of its files 
-fn stringify_directory(dir:&structures::Directory) -> String {
-
-    // creating headline for given directory -> taking only its name
-    let headline:String = format!(
-        "{} {}\n",
-        "#",
-        // "#".repeat(depth),
-        dir.name 
-    );
-    // traversing each file and directory
-    let mut resulting_string = String::new();
-    // only pushing headline if the folder is not empty!
-    if contains_md_file(dir){ 
-        resulting_string.push_str(&headline);
-    };
-
-    for file in  &dir.files{
-        // skipping if extension is mismatching
-        match file.extension {
-            FileExtension::Markdown => {
-                let file_link:String = format!("- [{}]({})\n",file.name,file.relative_path.display());
-                resulting_string.push_str(&file_link)
-                },
-            _ => {},
-        }
-    };
-    return resulting_string;
-
-    }
-
 //  ------ 
 //  ------HELPER FUNCTIONS------ 
 //  ------ 
@@ -383,7 +734,7 @@ fn enforce_filepath(function_to_enforce:fn() -> Result<PathBuf,Box<dyn std::erro
             }
         }
     };
-    return valid_path
+    valid_path
 }
 
 fn request_filepath() -> Result<PathBuf,Box<dyn Error>> { 
@@ -402,6 +753,15 @@ fn request_save_file() -> Result<PathBuf,Box<dyn Error>> {
     request_valid_path(true,false)
 }
 
+fn request_config_path() -> Result<PathBuf,Box<dyn Error>> { 
+    println!("\nenter path of config note");
+    let valid_path = request_valid_path(true,false)?;
+    if !valid_path.is_file() {
+        return Err("config note does not exist".into())
+    }
+    Ok(valid_path)
+}
+
 fn request_valid_path(is_file:bool,is_unique:bool) -> Result<PathBuf,Box<dyn std::error::Error>> { 
 
     // println!(":{prompt}");
@@ -424,10 +784,10 @@ fn request_valid_path(is_file:bool,is_unique:bool) -> Result<PathBuf,Box<dyn std
     if  valid_path.is_file() && is_file && is_unique {
         return Err("provided path exists already".into())
     }
-    if is_file && !valid_path.exists(){
-        return Ok(valid_path)
+    if is_file && (valid_path.is_file() || !valid_path.exists()){
+        Ok(valid_path)
     } else {
-        return Err("no valid path given".into())
+        Err("no valid path given".into())
     }
 
 }
@@ -435,7 +795,11 @@ fn request_valid_path(is_file:bool,is_unique:bool) -> Result<PathBuf,Box<dyn std
     // let added_extension_path = format!("{}Summary.md",trimmed_path);
 
 
-fn save_to_file(file_path: &PathBuf, content: String) -> Result<(), Box<dyn std::error::Error>> {
+fn save_to_file(file_path: &Path, content: String) -> Result<(), Box<dyn std::error::Error>> {
+        // destinations of profiles are usually created by the first run
+        if let Some(parent) = file_path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
         // Open the file in write mode, creating it if it doesn't exist
         let mut file = File::create(file_path)?;
 
@@ -448,10 +812,3 @@ fn save_to_file(file_path: &PathBuf, content: String) -> Result<(), Box<dyn std:
         Ok(())
 }
 
-fn read_from_file(file_path:&PathBuf) -> Result<BufReader<File>,Box<dyn std::error::Error>> {
-
-    let file = File::open(file_path)?;
-
-    let reader = BufReader::new(file);
-    Ok(reader)
-}
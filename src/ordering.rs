@@ -0,0 +1,343 @@
+//! contains logic to sort chapters and sections deterministically
+//!
+//! strategies, selected by `--order` or `order` in the `[output]` section of the project file:
+//! - `natural`: by name, numbers are compared by value so "2_intro" comes before "10_outro" (default)
+//! - `frontmatter`: by the `order` (or `weight`) property, directories by the one of their folder note
+//! - `created` / `modified`: by the `date-created` / `date-modified` property (`2024-06-08 10:46:38`),
+//!   oldest first. directories by the one of their folder note. as cloning or syncing a vault
+//!   resets the timestamps of the file system, they are only used for entries without property
+//!
+//! entries without a value for the strategy follow those with one, ties are sorted naturally.
+//! entries matching the `chapter_order` section of the config note come first,
+//! in the order of the section:
+//!
+//! ```markdown
+//! conf-start:chapter_order
+//! - introduction
+//! - notes/setup*
+//! conf-end:
+//! ```
+
+// internal imports
+use crate::patterns::PatternSet;
+use crate::structures::{Config, ConfigType, Directory, FileData};
+
+// external imports
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const ORDER_PROPERTIES: [&str; 2] = ["order", "weight"];
+const CREATED_PROPERTIES: [&str; 2] = ["date-created", "created"];
+const MODIFIED_PROPERTIES: [&str; 2] = ["date-modified", "modified"];
+
+#[derive(Deserialize, ValueEnum, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStrategy {
+    #[default]
+    Natural,
+    Frontmatter,
+    Created,
+    Modified,
+}
+
+/// explicit order given in `chapter_order`, one pattern per position
+#[derive(Default)]
+pub struct ExplicitOrder {
+    positions: Vec<PatternSet>,
+}
+
+struct SortKey {
+    position: usize,
+    value: Option<f64>,
+    name: String,
+}
+
+impl ExplicitOrder {
+    /// collects the entries of every `chapter_order` section in order
+    pub fn from_configs(configurations: &[Config]) -> Result<ExplicitOrder, Box<dyn Error>> {
+        let mut order = ExplicitOrder::default();
+        let entries = configurations
+            .iter()
+            .filter(|config| matches!(config.conf_type, ConfigType::ChapterOrder))
+            .flat_map(|config| &config.collection_of_options);
+        for entry in entries {
+            let mut pattern = PatternSet::default();
+            pattern.push(entry)?;
+            order.positions.push(pattern);
+        }
+        Ok(order)
+    }
+
+    /// position of the first matching entry, unlisted paths are placed last
+    fn position(&self, relative_path: &Path, is_directory: bool) -> usize {
+        // "introduction" should match "introduction.md" as well
+        let without_extension = relative_path.with_extension("");
+        self.positions
+            .iter()
+            .position(|pattern| {
+                pattern.matches(relative_path, is_directory)
+                    || (!is_directory && pattern.matches(&without_extension, false))
+            })
+            .unwrap_or(usize::MAX)
+    }
+}
+
+// ---
+// CORE FUNCTIONS
+// ---
+
+/// sorts files and sub directories of given directory
+/// the strategy of an override file takes precedence over the given one
+/// IMPORTANT: Conceptualized as _recursive function_
+pub fn sort_directory(dir: &mut Directory, strategy: OrderStrategy, explicit: &ExplicitOrder) {
+    let strategy = dir.order.unwrap_or(strategy);
+    sort_by_keys(&mut dir.files, |file| file_key(file, strategy, explicit));
+    sort_by_keys(&mut dir.sub_directories, |sub_dir| directory_key(sub_dir, strategy, explicit));
+    for sub_directory in &mut dir.sub_directories {
+        sort_directory(sub_directory, strategy, explicit);
+    }
+}
+
+/// compares two names, sequences of digits are compared by their value
+///
+/// EXAMPLE:
+/// "2_intro" < "10_outro", "Note 9" < "note 10"
+pub fn natural_cmp(left: &str, right: &str) -> Ordering {
+    let left_chunks = chunks(left);
+    let right_chunks = chunks(right);
+    for (left_chunk, right_chunk) in left_chunks.iter().zip(&right_chunks) {
+        let ordering = match (is_number(left_chunk), is_number(right_chunk)) {
+            (true, true) => {
+                let left_number = left_chunk.trim_start_matches('0');
+                let right_number = right_chunk.trim_start_matches('0');
+                left_number.len().cmp(&right_number.len()).then_with(|| left_number.cmp(right_number))
+            }
+            _ => left_chunk.to_lowercase().cmp(&right_chunk.to_lowercase()),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    left_chunks.len().cmp(&right_chunks.len()).then_with(|| left.cmp(right))
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+fn file_key(file: &FileData, strategy: OrderStrategy, explicit: &ExplicitOrder) -> SortKey {
+    SortKey {
+        position: explicit.position(&file.relative_path, false),
+        value: match strategy {
+            OrderStrategy::Natural => None,
+            OrderStrategy::Frontmatter => frontmatter_order(file),
+            OrderStrategy::Created | OrderStrategy::Modified => {
+                date_property(file, strategy).or_else(|| timestamp(&file.original_path, strategy))
+            }
+        },
+        name: file.name.clone(),
+    }
+}
+
+fn directory_key(dir: &Directory, strategy: OrderStrategy, explicit: &ExplicitOrder) -> SortKey {
+    SortKey {
+        position: explicit.position(&dir.relative_path, true),
+        value: match strategy {
+            OrderStrategy::Natural => None,
+            OrderStrategy::Frontmatter => dir.folder_note().and_then(frontmatter_order),
+            OrderStrategy::Created | OrderStrategy::Modified => dir
+                .folder_note()
+                .and_then(|folder_note| date_property(folder_note, strategy))
+                .or_else(|| timestamp(&dir.path, strategy)),
+        },
+        name: dir.name.clone(),
+    }
+}
+
+fn frontmatter_order(file: &FileData) -> Option<f64> {
+    ORDER_PROPERTIES
+        .iter()
+        .find_map(|key| file.frontmatter.get_text(key)?.trim().parse::<f64>().ok())
+        .filter(|value| value.is_finite())
+}
+
+/// date of the note as seconds since the unix epoch, taken from its properties
+fn date_property(file: &FileData, strategy: OrderStrategy) -> Option<f64> {
+    let keys = match strategy {
+        OrderStrategy::Created => CREATED_PROPERTIES,
+        _ => MODIFIED_PROPERTIES,
+    };
+    keys.iter().find_map(|key| parse_date(file.frontmatter.get_text(key)?))
+}
+
+/// seconds since the unix epoch of a date as written by obsidian, read as utc
+/// time zones and fractions of seconds are ignored
+///
+/// EXAMPLE:
+/// "2024-06-08 10:46:38", "2024-06-08T10:46", "2024-06-08"
+fn parse_date(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (date, time) = value.split_once([' ', 'T']).unwrap_or((value, ""));
+    let date: Vec<i64> = date.split('-').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let [year, month, day] = date[..] else {
+        return None;
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let time = time.trim().split(['Z', '+', '.']).next().unwrap_or("");
+    let seconds: i64 = match time.is_empty() {
+        true => 0,
+        false => {
+            let parts: Vec<i64> = time.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+            if parts.len() > 3 {
+                return None;
+            }
+            parts.iter().zip([3600, 60, 1]).map(|(value, factor)| value * factor).sum()
+        }
+    };
+    Some((days_since_epoch(year, month, day) * 86_400 + seconds) as f64)
+}
+
+/// days between 1970-01-01 and the given date of the gregorian calendar
+fn days_since_epoch(year: i64, month: i64, day: i64) -> i64 {
+    // years start in march, so the leap day is the last day of a year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// seconds since the unix epoch, None if the file system does not provide the timestamp
+fn timestamp(path: &Path, strategy: OrderStrategy) -> Option<f64> {
+    let metadata = fs::metadata(path).ok()?;
+    let time: SystemTime = match strategy {
+        OrderStrategy::Created => metadata.created().ok()?,
+        _ => metadata.modified().ok()?,
+    };
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_secs_f64())
+}
+
+/// sorts by explicit position, then by value (missing values last), then naturally by name
+fn compare_keys(left: &SortKey, right: &SortKey) -> Ordering {
+    left.position
+        .cmp(&right.position)
+        .then_with(|| match (left.value, right.value) {
+            (Some(left_value), Some(right_value)) => left_value.total_cmp(&right_value),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        .then_with(|| natural_cmp(&left.name, &right.name))
+}
+
+/// computes every key once, reading metadata on each comparison would be wasteful
+fn sort_by_keys<T>(items: &mut Vec<T>, key: impl Fn(&T) -> SortKey) {
+    let mut keyed: Vec<(SortKey, T)> = items.drain(..).map(|item| (key(&item), item)).collect();
+    keyed.sort_by(|(left, _), (right, _)| compare_keys(left, right));
+    items.extend(keyed.into_iter().map(|(_, item)| item));
+}
+
+/// splits a name into alternating sequences of digits and other characters
+fn chunks(name: &str) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut previous_digit = None;
+    for (index, ch) in name.char_indices() {
+        let digit = ch.is_ascii_digit();
+        if previous_digit.is_some_and(|previous| previous != digit) {
+            chunks.push(&name[start..index]);
+            start = index;
+        }
+        previous_digit = Some(digit);
+    }
+    if start < name.len() {
+        chunks.push(&name[start..]);
+    }
+    chunks
+}
+
+fn is_number(chunk: &str) -> bool {
+    chunk.starts_with(|ch: char| ch.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::parse_frontmatter_lines;
+    use crate::structures::FileExtension;
+    use std::path::PathBuf;
+
+    fn note(name: &str, properties: &[&str]) -> FileData {
+        let lines: Vec<String> = properties.iter().map(|line| line.to_string()).collect();
+        FileData {
+            original_path: PathBuf::from("/nonexistent").join(name),
+            dest_path: PathBuf::from(name),
+            relative_path: PathBuf::from(name),
+            relative_dest_path: PathBuf::from(name),
+            extension: FileExtension::Markdown,
+            name: name.to_string(),
+            title: name.to_string(),
+            frontmatter: parse_frontmatter_lines(&lines),
+            draft: false,
+        }
+    }
+
+    fn sorted_names(files: Vec<FileData>, strategy: OrderStrategy) -> Vec<String> {
+        let mut dir = Directory {
+            path: PathBuf::from("/nonexistent"),
+            name: String::new(),
+            title: String::new(),
+            dest_path: PathBuf::new(),
+            relative_path: PathBuf::new(),
+            relative_dest_path: PathBuf::new(),
+            sub_directories: Vec::new(),
+            files,
+            folder_note: None,
+            order: None,
+        };
+        sort_directory(&mut dir, strategy, &ExplicitOrder::default());
+        dir.files.into_iter().map(|file| file.name).collect()
+    }
+
+    #[test]
+    fn compares_numbers_by_value() {
+        assert_eq!(natural_cmp("2_intro", "10_outro"), Ordering::Less);
+        assert_eq!(natural_cmp("Note 9", "note 10"), Ordering::Less);
+        assert_eq!(natural_cmp("b", "a10"), Ordering::Greater);
+        let names = vec![note("10 end.md", &[]), note("2 middle.md", &[]), note("1 start.md", &[])];
+        assert_eq!(sorted_names(names, OrderStrategy::Natural), ["1 start.md", "2 middle.md", "10 end.md"]);
+    }
+
+    #[test]
+    fn orders_by_date_properties() {
+        let notes = || {
+            vec![
+                note("a.md", &["date-created: 2024-06-08 10:46:38", "date-modified: 2024-06-01"]),
+                note("b.md", &["date-created: 2023-01-01 08:00:00", "date-modified: 2024-07-01"]),
+                note("c.md", &["created: 2024-06-08 09:00"]),
+            ]
+        };
+        assert_eq!(sorted_names(notes(), OrderStrategy::Created), ["b.md", "c.md", "a.md"]);
+        // c.md has neither property nor file, so it is placed last
+        assert_eq!(sorted_names(notes(), OrderStrategy::Modified), ["a.md", "b.md", "c.md"]);
+    }
+
+    #[test]
+    fn parses_obsidian_dates() {
+        assert_eq!(parse_date("1970-01-01"), Some(0.0));
+        assert_eq!(parse_date("2024-06-08 10:46:38"), Some(1_717_843_598.0));
+        assert_eq!(parse_date("2024-06-08T10:46:38.120Z"), Some(1_717_843_598.0));
+        assert_eq!(parse_date("2024-06-08T10:46"), Some(1_717_843_560.0));
+        assert_eq!(parse_date("2000-02-29"), Some(951_782_400.0));
+        assert_eq!(parse_date("yesterday"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+    }
+}
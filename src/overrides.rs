@@ -0,0 +1,169 @@
+//! contains logic to read per-directory override files inside of the vault
+//!
+//! any directory may contain a note `_publish.md` (settings as frontmatter properties,
+//! editable in obsidian's property editor) or a dotfile `.obs2mdbook` (same `key: value` lines):
+//!
+//! ```yaml
+//! ---
+//! exclude: false
+//! include_only: [intro, "lectures/"]
+//! title: Lecture Notes
+//! prefix: Part
+//! order: frontmatter
+//! ---
+//! ```
+//!
+//! - `exclude`: skips the directory and everything below it
+//! - `include_only`: patterns relative to the directory, only matching children are collected
+//! - `title`: heading of the directory, instead of its prettified name
+//! - `prefix`: prefix of the heading, instead of the one given by `prefixes_for_headlines`
+//! - `order`: strategy used to sort its chapters (see ordering.rs)
+//!
+//! `prefix` and `order` apply to the whole subtree, until a subdirectory sets its own.
+//! if both files exist, only `_publish.md` is read. override files are never copied.
+//! unknown settings are reported as error, `exclude` is rejected in the root of the vault
+
+// internal imports
+use crate::config_parser::closest_match;
+use crate::diagnostics::ObsError;
+use crate::frontmatter::{parse_frontmatter_lines, read_frontmatter, Frontmatter};
+use crate::ordering::OrderStrategy;
+use crate::patterns::PatternSet;
+
+// external imports
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const OVERRIDE_NOTE: &str = "_publish.md";
+pub const OVERRIDE_DOTFILE: &str = ".obs2mdbook";
+const KNOWN_SETTINGS: &[&str] = &["exclude", "include_only", "title", "prefix", "order"];
+
+/// settings of a single directory, including the ones inherited from its parents
+#[derive(Default)]
+pub struct DirectoryOverrides {
+    pub exclude: bool,
+    include_only: Option<PatternSet>,
+    pub title: Option<String>,
+    pub prefix: Option<String>,
+    pub order: Option<OrderStrategy>,
+}
+
+impl DirectoryOverrides {
+    /// reads the override file of the given directory and applies the inherited settings
+    /// without override file, only `prefix` and `order` are inherited
+    pub fn load(directory: &Path, inherited: &DirectoryOverrides) -> Result<DirectoryOverrides, ObsError> {
+        let local = match read_override_file(directory)? {
+            Some((path, properties)) => from_properties(&properties).map_err(|e| ObsError::config(e).at(&path))?,
+            None => DirectoryOverrides::default(),
+        };
+        Ok(DirectoryOverrides {
+            prefix: local.prefix.or_else(|| inherited.prefix.clone()),
+            order: local.order.or(inherited.order),
+            ..local
+        })
+    }
+
+    /// whether a child of the directory is collected, `name` is its file or directory name
+    pub fn includes_child(&self, name: &str, is_directory: bool) -> bool {
+        match &self.include_only {
+            Some(patterns) if is_directory => patterns.matches(Path::new(name), true),
+            Some(patterns) => patterns.matches_file(Path::new(name)),
+            None => true,
+        }
+    }
+}
+
+/// whether the given file name denotes an override file
+pub fn is_override_file(name: &str) -> bool {
+    name == OVERRIDE_NOTE || name == OVERRIDE_DOTFILE
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// returns the path and properties of the override file, if the directory has one
+fn read_override_file(directory: &Path) -> Result<Option<(PathBuf, Frontmatter)>, ObsError> {
+    let note = directory.join(OVERRIDE_NOTE);
+    if note.is_file() {
+        let properties = read_frontmatter(&note)
+            .map_err(|e| ObsError::traversal(format!("could not read override file\n {e}")).at(&note))?;
+        return Ok(Some((note, properties)));
+    }
+    let dotfile = directory.join(OVERRIDE_DOTFILE);
+    if dotfile.is_file() {
+        let content = fs::read_to_string(&dotfile)
+            .map_err(|e| ObsError::traversal(format!("could not read override file\n {e}")).at(&dotfile))?;
+        let lines: Vec<String> = content.lines().map(str::to_string).collect();
+        return Ok(Some((dotfile, parse_frontmatter_lines(&lines))));
+    }
+    Ok(None)
+}
+
+fn from_properties(properties: &Frontmatter) -> Result<DirectoryOverrides, String> {
+    if let Some(unknown) = properties.keys().find(|key| !KNOWN_SETTINGS.contains(key)) {
+        let suggestion = match closest_match(unknown, KNOWN_SETTINGS) {
+            Some(known) => format!("did you mean \"{known}\"?"),
+            None => format!("known settings are: {}", KNOWN_SETTINGS.join(", ")),
+        };
+        return Err(format!("unknown setting \"{unknown}\", {suggestion}"));
+    }
+    let include_only = match properties.has("include_only") {
+        true => {
+            let mut patterns = PatternSet::default();
+            for entry in properties.get_list("include_only") {
+                patterns.push(entry).map_err(|e| e.to_string())?;
+            }
+            Some(patterns)
+        }
+        false => None,
+    };
+    let order = properties
+        .get_text("order")
+        .map(|value| OrderStrategy::from_str(value, true).map_err(|_| format!("unknown order \"{value}\"")))
+        .transpose()?;
+    let text = |key: &str| properties.get_text(key).filter(|value| !value.is_empty()).map(str::to_string);
+    Ok(DirectoryOverrides {
+        exclude: properties.get_bool("exclude").unwrap_or(false),
+        include_only,
+        title: text("title"),
+        prefix: text("prefix"),
+        order,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_and_order_are_inherited_title_is_not() {
+        let root = std::env::temp_dir().join(format!("obs2mdbook-overrides-{}", std::process::id()));
+        let nested = root.join("lectures/week 1");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(OVERRIDE_NOTE), "---\ntitle: Lectures\nprefix: Part\norder: modified\n---\n").unwrap();
+        fs::write(nested.join(OVERRIDE_DOTFILE), "include_only: [intro]\n").unwrap();
+
+        let top = DirectoryOverrides::load(&root, &DirectoryOverrides::default()).unwrap();
+        let plain = DirectoryOverrides::load(&root.join("lectures"), &top).unwrap();
+        let week = DirectoryOverrides::load(&nested, &plain).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(top.title.as_deref(), Some("Lectures"));
+        assert_eq!(plain.title, None);
+        assert_eq!(week.prefix.as_deref(), Some("Part"));
+        assert!(week.order == Some(OrderStrategy::Modified));
+        assert!(week.includes_child("intro.md", false) && !week.includes_child("outro.md", false));
+        assert!(plain.includes_child("outro.md", false));
+    }
+
+    #[test]
+    fn reports_unknown_settings() {
+        let properties = parse_frontmatter_lines(&["exlude: true".to_string()]);
+        let error = from_properties(&properties).map(|_| ()).unwrap_err();
+        assert_eq!(error, "unknown setting \"exlude\", did you mean \"exclude\"?");
+        let properties = parse_frontmatter_lines(&["order: random".to_string()]);
+        assert_eq!(from_properties(&properties).map(|_| ()).unwrap_err(), "unknown order \"random\"");
+    }
+}
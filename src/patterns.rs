@@ -0,0 +1,279 @@
+//! contains logic to match vault-relative paths against gitignore-style patterns
+//!
+//! supported syntax:
+//! - `private`: no "/" --> matches a file or directory with this name on any level
+//! - `notes/private` or `/drafts`: containing "/" --> anchored at the vault root
+//! - `*` matches within a single path component, `?` a single character, `[a-z]` a class
+//! - `**` matches any number of directories (`**/drafts/**`, `notes/**`)
+//! - `drafts/`: trailing "/" --> matches directories only
+//! - `!keep.md`: negation, re-includes paths matched by a previous pattern
+//!
+//! the last matching pattern decides, just like in a .gitignore.
+//! file patterns without an extension also match markdown notes, so `secret`
+//! matches `secret.md` but neither `secrets.md` nor `library.md`
+
+// internal imports
+use crate::structures::{Config, ConfigType};
+
+// external imports
+use regex::Regex;
+use std::error::Error;
+use std::path::Path;
+
+struct Pattern {
+    regex: Regex,
+    negated: bool,
+    directory_only: bool,
+}
+
+/// ordered collection of patterns of a single config section
+#[derive(Default)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+}
+
+/// every pattern used to decide whether a path is traversed
+#[derive(Default)]
+pub struct Filters {
+    pub included_directories: PatternSet,
+    pub excluded_directories: PatternSet,
+    pub excluded_files: PatternSet,
+}
+
+impl Filters {
+    /// collects the patterns of every matching config section
+    /// multiple sections of the same type are concatenated in order
+    pub fn from_configs(configurations: &[Config]) -> Result<Filters, Box<dyn Error>> {
+        let mut filters = Filters::default();
+        for config in configurations {
+            let target = match config.conf_type {
+                ConfigType::IncludedDirectories => &mut filters.included_directories,
+                ConfigType::ExcludedDirectories => &mut filters.excluded_directories,
+                ConfigType::ExcludedFiles => &mut filters.excluded_files,
+                _ => continue,
+            };
+            for entry in &config.collection_of_options {
+                target.push(entry)?;
+            }
+        }
+        Ok(filters)
+    }
+
+    /// whether a directory is traversed
+    /// without included directories, every directory not excluded is traversed
+    pub fn includes_directory(&self, relative_path: &Path) -> bool {
+        let included = self.included_directories.is_empty()
+            || self.included_directories.matches(relative_path, true);
+        included && !self.excluded_directories.matches(relative_path, true)
+    }
+
+    /// whether a file is collected
+    /// file patterns matching one of its parent directories exclude it as well
+    pub fn includes_file(&self, relative_path: &Path) -> bool {
+        !self.excluded_files.matches_file(relative_path)
+    }
+}
+
+impl PatternSet {
+    /// compiles and appends a single pattern, empty ones are skipped
+    pub fn push(&mut self, entry: &str) -> Result<(), Box<dyn Error>> {
+        let entry = entry.trim();
+        let (negated, glob) = match entry.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, entry),
+        };
+        let directory_only = glob.ends_with('/');
+        let glob = glob.trim_end_matches('/');
+        if glob.is_empty() {
+            return Ok(());
+        }
+        let regex = Regex::new(&glob_to_regex(glob))
+            .map_err(|e| format!("invalid pattern \"{entry}\"\n {e}"))?;
+        self.patterns.push(Pattern {
+            regex,
+            negated,
+            directory_only,
+        });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// checks given vault-relative path against every pattern, the last match decides
+    pub fn matches(&self, relative_path: &Path, is_directory: bool) -> bool {
+        let path = path_to_string(relative_path);
+        let mut matched = false;
+        for pattern in &self.patterns {
+            if pattern.directory_only && !is_directory {
+                continue;
+            }
+            if pattern.regex.is_match(&path) {
+                matched = !pattern.negated;
+            }
+        }
+        matched
+    }
+
+    /// checks given vault-relative file path against every pattern, the last match decides
+    /// a pattern matches if it matches the file or one of its parent directories,
+    /// so "!keep.md" may re-include a file inside of "**/drafts/**"
+    pub fn matches_file(&self, relative_path: &Path) -> bool {
+        let path = path_to_string(relative_path);
+        // "secret" should match "secret.md" as well
+        let without_extension = path.strip_suffix(".md");
+        let parents: Vec<String> = relative_path
+            .ancestors()
+            .skip(1)
+            .filter(|directory| !directory.as_os_str().is_empty())
+            .map(path_to_string)
+            .collect();
+
+        let mut matched = false;
+        for pattern in &self.patterns {
+            let matches_parent = parents.iter().any(|parent| pattern.regex.is_match(parent));
+            let matches_file = !pattern.directory_only
+                && (pattern.regex.is_match(&path)
+                    || without_extension.is_some_and(|stem| pattern.regex.is_match(stem)));
+            if matches_file || matches_parent {
+                matched = !pattern.negated;
+            }
+        }
+        matched
+    }
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// converts a single glob to an anchored regex
+///
+/// EXAMPLE:
+/// "*.excalidraw.md" --> "^(?:.*/)?[^/]*\.excalidraw\.md$"
+/// "notes/**" --> "^notes(?:/.*)?$"
+fn glob_to_regex(glob: &str) -> String {
+    // patterns containing a "/" are relative to the vault root
+    let anchored = glob.contains('/');
+    let glob = glob.trim_start_matches('/');
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+
+    let chars: Vec<char> = glob.chars().collect();
+    let mut index = 0;
+    while index < chars.len() {
+        let rest: String = chars[index..].iter().collect();
+        if rest.starts_with("**/") {
+            regex.push_str("(?:.*/)?");
+            index += 3;
+        } else if rest == "/**" {
+            // "dir/**" matches the directory itself as well
+            regex.push_str("(?:/.*)?");
+            index += 3;
+        } else if rest.starts_with("**") {
+            regex.push_str(".*");
+            index += 2;
+        } else {
+            match chars[index] {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => match chars[index..].iter().position(|ch| *ch == ']') {
+                    Some(length) => {
+                        let class: String = chars[index + 1..index + length].iter().collect();
+                        let class = match class.strip_prefix('!') {
+                            Some(negated) => format!("^{negated}"),
+                            None => class,
+                        };
+                        regex.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                        index += length;
+                    }
+                    None => regex.push_str(r"\["),
+                },
+                ch => regex.push_str(&regex::escape(&ch.to_string())),
+            }
+            index += 1;
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+fn path_to_string(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern_set(entries: &[&str]) -> PatternSet {
+        let mut patterns = PatternSet::default();
+        for entry in entries {
+            patterns.push(entry).unwrap();
+        }
+        patterns
+    }
+
+    #[test]
+    fn translates_globs_to_anchored_regexes() {
+        assert_eq!(glob_to_regex("*.excalidraw.md"), r"^(?:.*/)?[^/]*\.excalidraw\.md$");
+        assert_eq!(glob_to_regex("notes/**"), "^notes(?:/.*)?$");
+        assert_eq!(glob_to_regex("/drafts"), "^drafts$");
+        assert_eq!(glob_to_regex("**/drafts/**"), "^(?:.*/)?drafts(?:/.*)?$");
+        assert_eq!(glob_to_regex("week[!0-4]?"), "^(?:.*/)?week[^0-4][^/]$");
+    }
+
+    #[test]
+    fn names_match_on_any_level_paths_only_at_the_root() {
+        let patterns = pattern_set(&["private", "notes/secret"]);
+        assert!(patterns.matches(Path::new("private"), true));
+        assert!(patterns.matches(Path::new("a/b/private"), true));
+        assert!(patterns.matches(Path::new("notes/secret"), true));
+        assert!(!patterns.matches(Path::new("other/notes/secret"), true));
+        assert!(!patterns.matches(Path::new("privateer"), true));
+    }
+
+    #[test]
+    fn double_asterisk_spans_directories() {
+        let patterns = pattern_set(&["**/drafts/**"]);
+        assert!(patterns.matches(Path::new("drafts"), true));
+        assert!(patterns.matches(Path::new("a/drafts/b/c"), true));
+        assert!(patterns.matches_file(Path::new("a/drafts/b/note.md")));
+        assert!(!patterns.matches_file(Path::new("a/drafted/note.md")));
+
+        let single = pattern_set(&["notes/*"]);
+        assert!(single.matches(Path::new("notes/a"), true));
+        assert!(!single.matches(Path::new("notes/a/b"), true));
+    }
+
+    #[test]
+    fn negation_reincludes_and_the_last_match_decides() {
+        let patterns = pattern_set(&["**/drafts/**", "!keep.md"]);
+        assert!(patterns.matches_file(Path::new("drafts/other.md")));
+        assert!(!patterns.matches_file(Path::new("drafts/keep.md")));
+
+        let excluded_again = pattern_set(&["!keep.md", "keep.md"]);
+        assert!(excluded_again.matches_file(Path::new("keep.md")));
+    }
+
+    #[test]
+    fn file_patterns_without_extension_match_notes() {
+        let patterns = pattern_set(&["secret"]);
+        assert!(patterns.matches_file(Path::new("secret.md")));
+        assert!(patterns.matches_file(Path::new("dir/secret.md")));
+        assert!(!patterns.matches_file(Path::new("secrets.md")));
+        assert!(!patterns.matches_file(Path::new("secret.png")));
+    }
+
+    #[test]
+    fn trailing_slash_matches_directories_only() {
+        let patterns = pattern_set(&["drafts/"]);
+        assert!(patterns.matches(Path::new("drafts"), true));
+        assert!(!patterns.matches(Path::new("drafts"), false));
+        assert!(patterns.matches_file(Path::new("drafts/note.md")));
+        assert!(!patterns.matches_file(Path::new("drafts.md")));
+    }
+}
@@ -0,0 +1,179 @@
+//! contains the mdbook preprocessor mode, converting obsidian-syntax at `mdbook build` time
+//!
+//! registered in book.toml as:
+//!
+//! ```toml
+//! [preprocessor.obsidian]
+//! command = "obs_to_mdbook preprocessor"
+//! pdf_embed = "link"    # settings of the `[content]` section of the project file
+//! ```
+//!
+//! mdbook first calls `obs_to_mdbook preprocessor supports <renderer>`, then passes
+//! `[context, book]` as json on stdin and expects the converted book as json on stdout.
+//! the preprocessor never writes to the book, the stylesheet for callouts is created
+//! once by `obs_to_mdbook preprocessor css [BOOK_DIR]`
+//! links are resolved against every file inside of the book's source directory,
+//! thus notes keep their paths and no SUMMARY.md is created
+
+// internal imports
+use crate::callouts::write_callout_css;
+use crate::cli::{PreprocessorArgs, PreprocessorCommand};
+use crate::content::{convert_markdown, ContentSettings, ConversionContext};
+use crate::diagnostics::{ErrorKind, ObsError};
+use crate::link_resolver::LinkIndex;
+
+// external imports
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// name of the preprocessor table in book.toml
+const PREPROCESSOR_NAME: &str = "obsidian";
+/// keys of the preprocessor table interpreted by mdbook itself
+const MDBOOK_KEYS: [&str; 5] = ["command", "renderers", "before", "after", "optional"];
+/// callouts and embeds produce html, other renderers would show it verbatim
+const SUPPORTED_RENDERERS: [&str; 2] = ["html", "markdown"];
+
+// ---
+// CORE FUNCTIONS
+// ---
+
+/// answers `supports` requests or converts the book passed on stdin
+/// messages are printed to stderr, as stdout carries the book
+pub fn run_preprocessor(args: &PreprocessorArgs) -> ExitCode {
+    match &args.command {
+        Some(PreprocessorCommand::Supports { renderer }) => {
+            return match SUPPORTED_RENDERERS.contains(&renderer.as_str()) {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            };
+        }
+        Some(PreprocessorCommand::Css { book_dir }) => {
+            let book_dir = book_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+            return match write_callout_css(&book_dir) {
+                Ok(css_path) => {
+                    eprintln!("created stylesheet for callouts: {}", css_path.display());
+                    ExitCode::SUCCESS
+                }
+                Err(error) => {
+                    eprintln!("failed to create stylesheet for callouts with following error \n {error}");
+                    ErrorKind::Io.exit_code()
+                }
+            };
+        }
+        None => (),
+    }
+    match preprocess(io::stdin(), io::stdout()) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{error}");
+            error.kind.exit_code()
+        }
+    }
+}
+
+/// reads `[context, book]`, converts the content of every chapter and writes the book
+fn preprocess(input: impl io::Read, output: impl io::Write) -> Result<(), ObsError> {
+    let (context, mut book): (Value, Value) = serde_json::from_reader(input)
+        .map_err(|e| ObsError::io(format!("could not parse input of mdbook\n {e}")))?;
+
+    let root = context.get("root").and_then(Value::as_str).unwrap_or(".");
+    let config = context.get("config");
+    let src = config
+        .and_then(|config| config.pointer("/book/src"))
+        .and_then(Value::as_str)
+        .unwrap_or("src");
+    let src_dir = Path::new(root).join(src);
+    let settings = content_settings(config.and_then(|config| config.pointer(&format!("/preprocessor/{PREPROCESSOR_NAME}"))))?;
+
+    let mut relative_paths = Vec::new();
+    collect_book_files(&src_dir, Path::new(""), &mut relative_paths)?;
+    let index = LinkIndex::from_files(&src_dir, relative_paths);
+
+    // mdbook 0.4 calls them sections, later versions items
+    for key in ["sections", "items"] {
+        if let Some(Value::Array(items)) = book.get_mut(key) {
+            convert_items(items, &index, &settings);
+        }
+    }
+    serde_json::to_writer(output, &book).map_err(|e| ObsError::io(format!("could not write book\n {e}")))
+}
+
+/// converts the content of every chapter in place, including nested ones
+/// chapters without path (drafts) have no content to convert
+fn convert_items(items: &mut [Value], index: &LinkIndex, settings: &ContentSettings) {
+    for chapter in items.iter_mut().filter_map(|item| item.get_mut("Chapter")) {
+        let path = chapter.get("path").and_then(Value::as_str).map(PathBuf::from);
+        if let (Some(path), Some(Value::String(content))) = (path, chapter.get_mut("content")) {
+            let warnings = RefCell::new(Vec::new());
+            let context = ConversionContext::new(index, settings, &path, &path, &warnings);
+            *content = convert_markdown(content, &context);
+            for warning in warnings.into_inner() {
+                eprintln!("{warning}");
+            }
+        }
+        if let Some(Value::Array(sub_items)) = chapter.get_mut("sub_items") {
+            convert_items(sub_items, index, settings);
+        }
+    }
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// reads the settings of the preprocessor table, ignoring keys used by mdbook
+fn content_settings(table: Option<&Value>) -> Result<ContentSettings, ObsError> {
+    let mut table: Map<String, Value> = table.and_then(Value::as_object).cloned().unwrap_or_default();
+    table.retain(|key, _| !MDBOOK_KEYS.contains(&key.as_str()));
+    serde_json::from_value(Value::Object(table))
+        .map_err(|e| ObsError::config(format!("invalid [preprocessor.{PREPROCESSOR_NAME}] in book.toml\n {e}")))
+}
+
+/// collects the paths of every file inside of the source directory, relative to it
+/// hidden files and directories (e.g. the manifest of a sync) are skipped
+fn collect_book_files(src_dir: &Path, relative_dir: &Path, relative_paths: &mut Vec<PathBuf>) -> Result<(), ObsError> {
+    let directory = src_dir.join(relative_dir);
+    let entries = fs::read_dir(&directory).map_err(|e| ObsError::traversal(e).at(&directory))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| ObsError::traversal(e).at(&directory))?;
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let relative_path = relative_dir.join(&name);
+        match entry.path().is_dir() {
+            true => collect_book_files(src_dir, &relative_path, relative_paths)?,
+            false => relative_paths.push(relative_path),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_chapters_like_copied_notes() {
+        let root = std::env::temp_dir().join(format!("obs2mdbook-preprocessor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/Other.md"), "# Other\n").unwrap();
+        let content = "---\ntags: [a]\n---\n# Note\nsee [[Other]]\n";
+        let context = json!({ "root": root, "config": { "book": { "src": "src" } } });
+        let book = json!({ "sections": [{ "Chapter": { "name": "Note", "content": content, "path": "Note.md", "sub_items": [] } }] });
+
+        let mut output = Vec::new();
+        preprocess(json!([context, book]).to_string().as_bytes(), &mut output).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let book: Value = serde_json::from_slice(&output).unwrap();
+        let converted = book.pointer("/sections/0/Chapter/content").and_then(Value::as_str).unwrap();
+        assert_eq!(converted, "# Note\nsee [Other](Other.md)\n");
+    }
+}
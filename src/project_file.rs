@@ -0,0 +1,241 @@
+//! contains logic to load the per-book project file `obs2mdbook.toml`
+//!
+//! the project file is meant to be versioned next to `book.toml` and holds the
+//! same settings as the command-line and the lists of the config note:
+//!
+//! ```toml
+//! [paths]
+//! vault = "/path/to/vault"
+//! dest = "src"
+//! summary = "src/SUMMARY.md"
+//! config = "/path/to/vault/config.md"
+//!
+//! [config]
+//! excluded_files = ["private"]
+//! included_directories = ["notes"]
+//!
+//! [content]
+//! pdf_embed = "link"
+//!
+//! [output]
+//! slugify = "kebab"
+//!
+//! [publish]
+//! include_tags = ["public"]
+//! ```
+//!
+//! relative paths are resolved against the directory containing the project file.
+//! lists are merged with the ones parsed from the config note, command-line
+//! arguments take precedence over every path given here.
+//!
+//! named profiles publish different books from the same vault (`--profile NAME`, `--all-profiles`):
+//!
+//! ```toml
+//! [profiles.website]
+//! paths = { dest = "../website/src", summary = "../website/src/SUMMARY.md" }
+//! config = { included_directories = ["public"] }
+//! output = { summary_style = "nested" }
+//! publish = { include_tags = ["public"] }
+//! ```
+//!
+//! a profile accepts every section of the project file and is applied on top of them:
+//! lists of its `config` section are appended to the shared ones, every other value replaces the shared one
+
+// internal imports
+use crate::content::ContentSettings;
+use crate::publish::PublishSettings;
+use crate::settings::OutputSettings;
+use crate::structures::{Config, ConfigType};
+
+// external imports
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const PROJECT_FILE_NAME: &str = "obs2mdbook.toml";
+const PROFILES_KEY: &str = "profiles";
+const CONFIG_KEY: &str = "config";
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectFile {
+    pub paths: ProjectPaths,
+    pub config: ProjectConfig,
+    pub content: ContentSettings,
+    pub output: OutputSettings,
+    pub publish: PublishSettings,
+    pub debug: bool,
+    pub strict: bool,
+    // settings of each profile, applied by `load_project_file`
+    pub profiles: BTreeMap<String, toml::Table>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectPaths {
+    pub vault: Option<PathBuf>,
+    pub dest: Option<PathBuf>,
+    pub summary: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+}
+
+/// same sections as supported by the config note, see config_parser.rs
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub excluded_files: Vec<String>,
+    pub included_directories: Vec<String>,
+    pub excluded_directories: Vec<String>,
+    pub prefixes_for_headlines: Vec<String>,
+    pub copy_paths: Vec<String>,
+    pub chapter_order: Vec<String>,
+}
+
+// ---
+// CORE FUNCTIONS
+// ---
+
+/// searches for a project file in the given directory and all of its parents
+/// returns the first one found
+pub fn find_project_file(start_dir: &Path) -> Option<PathBuf> {
+    start_dir
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
+
+/// reads and parses the given project file, with the settings of the given profile applied
+/// relative paths are resolved against the directory of the project file
+pub fn load_project_file(path: &Path, profile: Option<&str>) -> Result<ProjectFile, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut table: toml::Table = toml::from_str(&content)
+        .map_err(|e| format!("could not parse project file {}\n {e}", path.display()))?;
+    if let Some(name) = profile {
+        let profile_table = table
+            .get(PROFILES_KEY)
+            .and_then(|profiles| profiles.get(name))
+            .and_then(toml::Value::as_table)
+            .cloned()
+            .ok_or_else(|| format!("unknown profile \"{name}\", available: {}", profile_names(&table).join(", ")))?;
+        apply_profile(&mut table, profile_table, false);
+    }
+    let mut project: ProjectFile = toml::Value::Table(table)
+        .try_into()
+        .map_err(|e| format!("could not parse project file {}\n {e}", path.display()))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    for entry in [
+        &mut project.paths.vault,
+        &mut project.paths.dest,
+        &mut project.paths.summary,
+        &mut project.paths.config,
+    ] {
+        if let Some(relative) = entry.as_ref().filter(|p| p.is_relative()) {
+            *entry = Some(base_dir.join(relative));
+        }
+    }
+    Ok(project)
+}
+
+/// applies the settings of a profile on top of the shared ones
+/// tables are merged recursively, lists are appended if `append_lists` is set
+fn apply_profile(base: &mut toml::Table, profile: toml::Table, append_lists: bool) {
+    for (key, value) in profile {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(shared)), toml::Value::Table(overrides)) => {
+                apply_profile(shared, overrides, append_lists || key == CONFIG_KEY);
+            }
+            (Some(toml::Value::Array(shared)), toml::Value::Array(additions)) if append_lists => {
+                shared.extend(additions);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn profile_names(table: &toml::Table) -> Vec<String> {
+    table
+        .get(PROFILES_KEY)
+        .and_then(toml::Value::as_table)
+        .map(|profiles| profiles.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+impl ProjectConfig {
+    /// converts the lists of the project file to the representation used by the config note
+    /// empty lists are omitted
+    pub fn to_configs(&self) -> Vec<Config> {
+        [
+            (ConfigType::ExcludedFiles, &self.excluded_files),
+            (ConfigType::IncludedDirectories, &self.included_directories),
+            (ConfigType::ExcludedDirectories, &self.excluded_directories),
+            (ConfigType::PrefixHeadline, &self.prefixes_for_headlines),
+            (ConfigType::CollectedPaths, &self.copy_paths),
+            (ConfigType::ChapterOrder, &self.chapter_order),
+        ]
+        .into_iter()
+        .filter(|(_, options)| !options.is_empty())
+        .map(|(conf_type, options)| Config {
+            conf_type,
+            collection_of_options: options.clone(),
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summary::SummaryStyle;
+
+    const PROJECT: &str = r#"
+[paths]
+dest = "book/src"
+
+[config]
+excluded_files = ["private"]
+
+[output]
+summary_style = "parts"
+
+[publish]
+include_tags = ["book"]
+
+[profiles.website]
+paths = { dest = "website/src" }
+config = { excluded_files = ["drafts"], chapter_order = ["intro"] }
+output = { summary_style = "nested" }
+publish = { include_tags = ["public"] }
+"#;
+
+    fn project_file(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("obs2mdbook-{name}-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join(PROJECT_FILE_NAME);
+        fs::write(&path, PROJECT).unwrap();
+        path
+    }
+
+    #[test]
+    fn profiles_append_config_lists_and_replace_everything_else() {
+        let path = project_file("profile");
+        let shared = load_project_file(&path, None).unwrap();
+        let website = load_project_file(&path, Some("website")).unwrap();
+        let unknown = load_project_file(&path, Some("print")).map(|_| ()).unwrap_err();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(shared.config.excluded_files, ["private"]);
+        assert_eq!(shared.paths.dest, Some(path.parent().unwrap().join("book/src")));
+
+        assert_eq!(website.config.excluded_files, ["private", "drafts"]);
+        assert_eq!(website.config.chapter_order, ["intro"]);
+        assert_eq!(website.publish.include_tags, ["public"]);
+        assert!(website.output.summary_style == SummaryStyle::Nested);
+        assert_eq!(website.paths.dest, Some(path.parent().unwrap().join("website/src")));
+        assert!(unknown.to_string().contains("available: website"), "{unknown}");
+    }
+}
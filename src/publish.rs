@@ -0,0 +1,94 @@
+//! contains the rules deciding whether a note is published, based on its frontmatter
+//!
+//! may be configured in the `[publish]` section of the project file:
+//!
+//! ```toml
+//! [publish]
+//! property = "publish"      # `publish: false` excludes a note
+//! require_property = false  # true: only notes with `publish: true` are published
+//! include_tags = ["public"] # if set, only notes carrying one of these tags are published
+//! exclude_tags = ["private"]
+//! draft_property = "draft"  # `draft: true` marks a draft
+//! drafts = "chapter"        # "exclude" drafts or list them as draft chapters
+//! ```
+//!
+//! an explicit `publish: true` overrides `include_tags`, excluded tags always win
+//! only notes are subject to these rules, attachments (images, pdfs) are always collected
+
+// internal imports
+use crate::frontmatter::Frontmatter;
+
+// external imports
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PublishSettings {
+    pub property: String,
+    pub require_property: bool,
+    pub include_tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+    pub draft_property: String,
+    pub drafts: DraftMode,
+}
+
+impl Default for PublishSettings {
+    fn default() -> Self {
+        PublishSettings {
+            property: "publish".to_string(),
+            require_property: false,
+            include_tags: Vec::new(),
+            exclude_tags: Vec::new(),
+            draft_property: "draft".to_string(),
+            drafts: DraftMode::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DraftMode {
+    // drafts are neither listed nor copied
+    #[default]
+    Exclude,
+    // drafts are listed as draft chapters (`- [Title]()`) but not copied
+    Chapter,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PublishState {
+    Published,
+    Draft,
+    Excluded,
+}
+
+/// decides how a note is published based on its frontmatter
+pub fn publish_state(frontmatter: &Frontmatter, settings: &PublishSettings) -> PublishState {
+    let tags = frontmatter.tags();
+    let has_any_tag = |wanted: &[String]| {
+        wanted
+            .iter()
+            .any(|tag| tags.contains(&tag.trim_start_matches('#').to_lowercase()))
+    };
+
+    if has_any_tag(&settings.exclude_tags) {
+        return PublishState::Excluded;
+    }
+    match frontmatter.get_bool(&settings.property) {
+        Some(false) => return PublishState::Excluded,
+        Some(true) => {}
+        None if settings.require_property => return PublishState::Excluded,
+        None if !settings.include_tags.is_empty() && !has_any_tag(&settings.include_tags) => {
+            return PublishState::Excluded
+        }
+        None => {}
+    }
+
+    if frontmatter.get_bool(&settings.draft_property) == Some(true) {
+        return match settings.drafts {
+            DraftMode::Exclude => PublishState::Excluded,
+            DraftMode::Chapter => PublishState::Draft,
+        };
+    }
+    PublishState::Published
+}
@@ -1,12 +1,89 @@
 // File contains settings for modifying behavior of this "parser"
+// they are assembled at runtime from the command-line and the project file
+// see cli.rs and project_file.rs
 
-// denotes whether to ask for Paths or take the ones given in settings!
-pub const REQUEST_PATHS: bool = false;
-// denotes root path for obsidian vault
-pub const CONFIG_SOURCE: &str = "/Path/To/Config/For/Settings.md";
-pub const PATH_SOURCE: &str = "/Path/To/Source/Of/Vault";
-// denotes root path of destination
-pub const PATH_DEST: &str = "/Path/To/Destination/Directory";
-pub const PATH_SUMMARY: &str = "/Path/To/mdbook/SUMMARY.md";
+// internal imports
+use crate::content::ContentSettings;
+use crate::diagnostics::ObsError;
+use crate::dry_run::PlanFormat;
+use crate::ordering::{ExplicitOrder, OrderStrategy};
+use crate::patterns::Filters;
+use crate::publish::PublishSettings;
+use crate::slugify::SlugStrategy;
+use crate::summary::SummaryStyle;
+use crate::structures::{CollectedPaths, Config};
+use crate::titles::HeadlineRules;
 
-pub const PRINT_DEBUG:bool = true;
\ No newline at end of file
+// external imports
+use serde::Deserialize;
+use std::path::PathBuf;
+
+pub struct Settings {
+    // denotes root path of vault, destination and SUMMARY.md
+    pub paths: CollectedPaths,
+    // denotes path of config note inside the vault
+    // may be omitted if a project file supplies the configuration instead
+    pub config_source: Option<PathBuf>,
+    pub print_debug: bool,
+    // if set, nothing is written but the planned changes are printed
+    pub dry_run: Option<PlanFormat>,
+    // denotes how obsidian-syntax is converted
+    pub content: ContentSettings,
+    // denotes how files are named and listed in the mdbook
+    pub output: OutputSettings,
+    // denotes which notes are published based on their frontmatter
+    pub publish: PublishSettings,
+}
+
+/// rules parsed from the config sections of project file and config note
+/// reloaded if the config note changes while watching
+pub struct Rules {
+    pub filters: Filters,
+    pub headlines: HeadlineRules,
+    pub order: ExplicitOrder,
+}
+
+impl Rules {
+    /// IMPORTANT: multiple sections of the same type may exist, they are concatenated
+    pub fn from_configs(configurations: &[Config]) -> Result<Rules, ObsError> {
+        Ok(Rules {
+            filters: Filters::from_configs(configurations).map_err(ObsError::config)?,
+            headlines: HeadlineRules::from_configs(configurations).map_err(ObsError::config)?,
+            order: ExplicitOrder::from_configs(configurations).map_err(ObsError::config)?,
+        })
+    }
+}
+
+// may be set in the `[output]` section of the project file
+#[derive(Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSettings {
+    // denotes how names of copied files and directories are made url-safe
+    pub slugify: SlugStrategy,
+    // denotes layout of SUMMARY.md
+    pub summary_style: SummaryStyle,
+    // whether directory chapters without folder note get a generated index page
+    pub index_pages: bool,
+    // whether folder notes are detected and used as chapter of their directory
+    pub folder_notes: bool,
+    // whether johnny-decimal prefixes like "121-130_" are removed from chapter titles
+    pub strip_number_prefixes: bool,
+    // denotes how chapters and sections are sorted
+    pub order: OrderStrategy,
+    // whether only changed files are written and stale ones removed, see sync.rs
+    pub sync: bool,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        OutputSettings {
+            slugify: SlugStrategy::default(),
+            summary_style: SummaryStyle::default(),
+            index_pages: true,
+            folder_notes: true,
+            strip_number_prefixes: false,
+            order: OrderStrategy::default(),
+            sync: false,
+        }
+    }
+}
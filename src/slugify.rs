@@ -0,0 +1,132 @@
+//! contains logic to create url-safe names for copied files and directories
+//!
+//! strategies:
+//! - `none`: names are kept as they are, links are escaped instead
+//! - `kebab`: "My Note (draft).md" --> "my-note-draft.md"
+//! - `underscore`: "My Note (draft).md" --> "My_Note_draft.md"
+//!
+//! extensions of files are kept, so ".md" files are still recognized by mdbook.
+//! directories have no extension: "1. Basics/" --> "1.-basics/"
+
+// external imports
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Deserialize, ValueEnum, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlugStrategy {
+    #[default]
+    None,
+    Kebab,
+    Underscore,
+}
+
+/// slugifies every component of the given relative path
+/// only the last component of a file path keeps an extension
+pub fn slugify_path(path: &Path, strategy: SlugStrategy, is_file: bool) -> PathBuf {
+    let count = path.components().count();
+    path.components()
+        .enumerate()
+        .map(|(position, component)| match component {
+            Component::Normal(name) if is_file && position + 1 == count => {
+                slugify_name(&name.to_string_lossy(), strategy)
+            }
+            Component::Normal(name) => slugify_directory_name(&name.to_string_lossy(), strategy),
+            other => other.as_os_str().to_string_lossy().to_string(),
+        })
+        .collect()
+}
+
+/// slugifies a single file name, keeping its extension
+pub fn slugify_name(name: &str, strategy: SlugStrategy) -> String {
+    // dotfiles such as ".trash" have no extension
+    let (stem, extension) = match name.rfind('.') {
+        Some(position) if position > 0 => (&name[..position], &name[position..]),
+        _ => (name, ""),
+    };
+    match slugify_stem(stem, strategy) {
+        Some(slug) => format!("{slug}{}", extension.to_lowercase()),
+        None => name.to_string(),
+    }
+}
+
+/// slugifies a single directory name, dots are part of the name
+///
+/// EXAMPLE:
+/// kebab: "1. Basics" --> "1.-basics"
+fn slugify_directory_name(name: &str, strategy: SlugStrategy) -> String {
+    slugify_stem(name, strategy).unwrap_or_else(|| name.to_string())
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// returns `None` if nothing is left of the name or no strategy is set
+fn slugify_stem(stem: &str, strategy: SlugStrategy) -> Option<String> {
+    let separator = match strategy {
+        SlugStrategy::None => return None,
+        SlugStrategy::Kebab => '-',
+        SlugStrategy::Underscore => '_',
+    };
+
+    let mut slug = String::with_capacity(stem.len());
+    for ch in stem.chars() {
+        let ch = match strategy {
+            SlugStrategy::Kebab => ch.to_lowercase().next().unwrap_or(ch),
+            _ => ch,
+        };
+        if ch.is_alphanumeric() || ch == '.' || ch == '_' || ch == '-' {
+            slug.push(ch);
+        } else if !slug.ends_with(separator) {
+            slug.push(separator);
+        }
+    }
+    let slug = slug.trim_matches(separator);
+    match slug.is_empty() {
+        true => None,
+        false => Some(slug.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugifies_file_names_keeping_the_extension() {
+        assert_eq!(slugify_name("My Note (draft).md", SlugStrategy::Kebab), "my-note-draft.md");
+        assert_eq!(slugify_name("My Note (draft).md", SlugStrategy::Underscore), "My_Note_draft.md");
+        assert_eq!(slugify_name("My Note (draft).md", SlugStrategy::None), "My Note (draft).md");
+        assert_eq!(slugify_name("Diagram.PNG", SlugStrategy::Kebab), "diagram.png");
+        assert_eq!(slugify_name("v1.2 notes.md", SlugStrategy::Kebab), "v1.2-notes.md");
+    }
+
+    #[test]
+    fn keeps_names_without_slug() {
+        assert_eq!(slugify_name(".trash", SlugStrategy::Kebab), ".trash");
+        assert_eq!(slugify_name("???.md", SlugStrategy::Kebab), "???.md");
+    }
+
+    #[test]
+    fn directories_have_no_extension() {
+        let path = slugify_path(Path::new("1. Basics/Intro Part.md"), SlugStrategy::Kebab, true);
+        assert_eq!(path, PathBuf::from("1.-basics/intro-part.md"));
+        let directory = slugify_path(Path::new("Week 1/2. Lists"), SlugStrategy::Kebab, false);
+        assert_eq!(directory, PathBuf::from("week-1/2.-lists"));
+    }
+
+    #[test]
+    fn different_names_may_collide() {
+        // such collisions are reported when planning the sync, see sync.rs
+        assert_eq!(
+            slugify_name("My Note.md", SlugStrategy::Kebab),
+            slugify_name("my-note.md", SlugStrategy::Kebab)
+        );
+        assert_ne!(
+            slugify_name("My Note.md", SlugStrategy::Underscore),
+            slugify_name("my-note.md", SlugStrategy::Underscore)
+        );
+    }
+}
@@ -1,8 +1,13 @@
-/// holds several structs that are necessary to interact and represent data
-/// 
+//! holds several structs that are necessary to interact and represent data
+//! 
+
+// internal imports
+use crate::copy_paths::PathMapping;
+use crate::frontmatter::Frontmatter;
+use crate::ordering::OrderStrategy;
 
 // external imports
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// denotes a directory holding information about it 
 /// contains all sub-dirs and files as vector 
@@ -10,10 +15,38 @@ pub struct Directory {
 
     pub path: PathBuf,
     pub name: String,
+    // heading in SUMMARY.md, transformed by `prefixes_for_headlines`
+    pub title: String,
     pub dest_path: PathBuf,
     pub relative_path: PathBuf,
+    // relative path inside the destination, differs from relative_path if names are slugified
+    pub relative_dest_path: PathBuf,
     pub sub_directories:Vec<Directory>,
     pub files:Vec<FileData>,
+    // name of the file in `files` representing the directory itself
+    // e.g. "Folder/Folder.md", "Folder/index.md" or "Folder/README.md"
+    pub folder_note: Option<String>,
+    // strategy sorting its chapters, set by an override file of the directory or of a parent
+    pub order: Option<OrderStrategy>,
+}
+
+impl Directory {
+    pub fn folder_note(&self) -> Option<&FileData> {
+        let name = self.folder_note.as_ref()?;
+        self.files.iter().find(|file| &file.name == name)
+    }
+
+    pub fn is_folder_note(&self, file:&FileData) -> bool {
+        self.folder_note.as_ref() == Some(&file.name)
+    }
+
+    /// searches the collected file with given original path, recursively
+    pub fn find_file(&self, original_path:&Path) -> Option<&FileData> {
+        self.files
+            .iter()
+            .find(|file| file.original_path == original_path)
+            .or_else(|| self.sub_directories.iter().find_map(|dir| dir.find_file(original_path)))
+    }
 }
 
 
@@ -23,8 +56,15 @@ pub struct FileData {
     pub original_path: PathBuf,
     pub dest_path: PathBuf,
     pub relative_path: PathBuf,
+    pub relative_dest_path: PathBuf,
     pub extension: FileExtension,
-    pub name:String
+    pub name:String,
+    // chapter title: frontmatter title, first heading or prettified name
+    pub title:String,
+    // properties of markdown files, empty for every other file
+    pub frontmatter: Frontmatter,
+    // drafts are listed as draft chapter but neither copied nor linked
+    pub draft: bool,
 }
 
 pub enum FileExtension {
@@ -39,7 +79,9 @@ pub enum FileExtension {
 pub struct CollectedPaths {
     pub root_dir: PathBuf,
     pub dest_dir: PathBuf,
-    pub dest_file: PathBuf
+    pub dest_file: PathBuf,
+    // relocations of vault directories inside of dest_dir, see copy_paths.rs
+    pub mappings: Vec<PathMapping>,
 }
 
 /// FIXME --> Unkown is rather ambigous and prone to produce errors 
@@ -56,13 +98,12 @@ pub fn fileextension_to_string(extension:&FileExtension) -> String {
 /// takes extension as string and converts to FileExtension Struct
 /// attention: each string is prefixed with a "." and has to be 
 /// FIXME --> Unkown is rather ambigous and prone to produce errors 
-pub fn string_to_fileextension(value:&String) -> FileExtension { 
-    match value.as_str() {
+pub fn string_to_fileextension(value:&str) -> FileExtension { 
+    match value.to_lowercase().as_str() {
         "md" => FileExtension::Markdown,
         "pdf" => FileExtension::Pdf,
         "html" => FileExtension::Html,
-        "jpg" => FileExtension::Image,
-        "png" => FileExtension::Image,
+        "jpg" | "jpeg" | "png" | "gif" | "svg" | "webp" | "bmp" => FileExtension::Image,
         _ => FileExtension::Unknown
 
     }
@@ -76,7 +117,9 @@ pub struct Config {
 
 pub enum ConfigType {
     IncludedDirectories,
+    ExcludedDirectories,
     ExcludedFiles,
     PrefixHeadline,
-    CollectedPaths
+    CollectedPaths,
+    ChapterOrder,
 }
\ No newline at end of file
@@ -0,0 +1,325 @@
+//! contains logic to create SUMMARY.md from the collected `Directory`
+//!
+//! two styles are available:
+//! - `parts`: every directory becomes a part title (`# dirname`) followed by a flat list of its notes
+//! - `nested`: directories become parent chapters, their content is indented by depth
+//!
+//! a directory chapter is backed by its folder note (`Folder/Folder.md`, `Folder/index.md`
+//! or `Folder/README.md`), which is not listed among its children again.
+//! without folder note, a generated `index.md` listing its children is used
+//! (see `collect_index_pages`) or - if disabled - the directory becomes a draft chapter.
+//! if a note of the directory already takes `index.md` (e.g. with `folder_notes = false`),
+//! the generated page is named `_index.md` instead.
+//! in the `parts` style the folder note is the first chapter below the part title,
+//! every other note of the directory is nested below it
+
+// internal imports
+use crate::link_resolver::{relative_link, summary_link};
+use crate::settings::OutputSettings;
+use crate::structures::{Directory, FileData, FileExtension};
+use crate::sync::{OutputSource, PlannedFile};
+
+// external imports
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+const INDEX_FILE: &str = "index.md";
+// name of the generated page, if a note of the directory is copied to INDEX_FILE
+const FALLBACK_INDEX_FILE: &str = "_index.md";
+const README_FILE: &str = "README.md";
+const INDENTATION: &str = "    ";
+
+#[derive(Deserialize, ValueEnum, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryStyle {
+    #[default]
+    Parts,
+    Nested,
+}
+
+/// converts given Directory instance to string for mdbook
+/// wrapper for extract_file_representation_from_dir
+/// uses structure for SUMMARY.md for mdbook
+pub fn create_book_summary(directory_data:&Directory, settings:&OutputSettings) -> String {
+
+    let directory_as_string:String = match settings.summary_style {
+        SummaryStyle::Parts => extract_file_representation_from_dir(directory_data),
+        SummaryStyle::Nested => nest_directory(directory_data, 0, settings),
+    };
+    format!("# SUMMARY.MD Structure\n\n{} ",directory_as_string)
+}
+
+// ---
+// PARTS STYLE
+// ---
+
+/// traverses Directory instance, converts to string complying for summary of mdbooks
+/// IMPORTANT: Conceptualized as _recursive function_
+fn extract_file_representation_from_dir(active_dir:&Directory) -> String {
+
+    let mut dir_as_string:String = String::new();
+
+    // traversing and processing the active directory
+    let stringified_dir: String = stringify_directory(active_dir);
+
+    dir_as_string.push_str(&stringified_dir);
+
+    // traversing all subsequent directories
+    for directory in &active_dir.sub_directories {
+        let dir_string = extract_file_representation_from_dir(directory);
+        dir_as_string.push_str(&dir_string);
+    }
+
+    dir_as_string
+}
+
+/// converts a Directory to string representation of its files 
+fn stringify_directory(dir:&Directory) -> String {
+
+    // creating headline for given directory -> taking only its name
+    let headline:String = format!(
+        "{} {}\n",
+        "#",
+        dir.title
+    );
+    // traversing each file and directory
+    let mut resulting_string = String::new();
+    // only pushing headline if the folder is not empty!
+    if contains_md_file(dir){ 
+        resulting_string.push_str(&headline);
+    };
+
+    // folder note becomes parent chapter of every other note
+    match dir.folder_note() {
+        Some(folder_note) => {
+            resulting_string.push_str(&format!("- [{}]({})\n", escape_title(&dir.title), chapter_link(folder_note)));
+            resulting_string.push_str(&stringify_files(dir, INDENTATION));
+        },
+        None => resulting_string.push_str(&stringify_files(dir, "")),
+    }
+    resulting_string
+}
+
+// ---
+// NESTED STYLE
+// ---
+
+/// lists files of the given directory at the given depth,
+/// followed by its sub directories as parent chapters one level deeper
+/// IMPORTANT: Conceptualized as _recursive function_
+fn nest_directory(dir:&Directory, depth:usize, settings:&OutputSettings) -> String {
+    let indentation = INDENTATION.repeat(depth);
+    let mut resulting_string = stringify_files(dir, &indentation);
+
+    for sub_directory in dir.sub_directories.iter().filter(|sub_dir| contains_md_file_recursive(sub_dir)) {
+        let link = match index_page(sub_directory, settings) {
+            Some(index) => summary_link(&index),
+            None => String::new(),
+        };
+        resulting_string.push_str(&format!("{indentation}- [{}]({link})\n", escape_title(&sub_directory.title)));
+        resulting_string.push_str(&nest_directory(sub_directory, depth + 1, settings));
+    }
+    resulting_string
+}
+
+/// returns destination-relative path of the page backing the directory chapter
+/// None if the directory is listed as draft chapter
+fn index_page(dir:&Directory, settings:&OutputSettings) -> Option<PathBuf> {
+    match dir.folder_note() {
+        Some(folder_note) if !folder_note.draft => Some(folder_note.relative_dest_path.clone()),
+        Some(_) => None,
+        None if settings.index_pages => Some(generated_index_path(dir)),
+        None => None,
+    }
+}
+
+/// collects an index page for every directory chapter without folder note
+/// only required for the nested style
+pub fn collect_index_pages(dir:&Directory, settings:&OutputSettings, outputs:&mut Vec<PlannedFile>) {
+    if settings.summary_style != SummaryStyle::Nested || !settings.index_pages {
+        return;
+    }
+    for sub_directory in dir.sub_directories.iter().filter(|sub_dir| contains_md_file_recursive(sub_dir)) {
+        if sub_directory.folder_note.is_none() {
+            outputs.push(PlannedFile {
+                relative_dest_path: generated_index_path(sub_directory),
+                source: OutputSource::Content(create_index_page(sub_directory, settings)),
+                origin: sub_directory.path.clone(),
+            });
+        }
+        collect_index_pages(sub_directory, settings, outputs);
+    }
+}
+
+/// lists every note and sub directory of the given directory
+fn create_index_page(dir:&Directory, settings:&OutputSettings) -> String {
+    let index_path = generated_index_path(dir);
+    let mut page = format!("# {}\n\n", dir.title);
+
+    for file in dir.files.iter().filter(|file| !file.draft) {
+        if let FileExtension::Markdown = file.extension {
+            let link = relative_link(&index_path, &file.relative_dest_path);
+            page.push_str(&format!("- [{}]({link})\n", escape_title(&file.title)));
+        }
+    }
+    for sub_directory in dir.sub_directories.iter().filter(|sub_dir| contains_md_file_recursive(sub_dir)) {
+        let link = match index_page(sub_directory, settings) {
+            Some(sub_index) => relative_link(&index_path, &sub_index),
+            None => continue,
+        };
+        page.push_str(&format!("- [{}]({link})\n", escape_title(&sub_directory.title)));
+    }
+    page
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+/// destination-relative path of the generated index page of given directory
+/// falls back to FALLBACK_INDEX_FILE, so a note copied to "Folder/index.md" is never overwritten
+fn generated_index_path(dir:&Directory) -> PathBuf {
+    let index_path = dir.relative_dest_path.join(INDEX_FILE);
+    match dir.files.iter().any(|file| file.relative_dest_path == index_path) {
+        true => dir.relative_dest_path.join(FALLBACK_INDEX_FILE),
+        false => index_path,
+    }
+}
+
+/// searches the folder note of given directory
+/// candidates in order of precedence: "Folder/Folder.md", "Folder/index.md", "Folder/README.md"
+pub fn find_folder_note(dir:&Directory) -> Option<String> {
+    let own_name = format!("{}.md", dir.name);
+    [own_name.as_str(), INDEX_FILE, README_FILE]
+        .iter()
+        .find_map(|candidate| {
+            dir.files.iter().find(|file| {
+                matches!(file.extension, FileExtension::Markdown) && file.name.eq_ignore_ascii_case(candidate)
+            })
+        })
+        .map(|file| file.name.clone())
+}
+
+/// lists every markdown file of the directory except its folder note,
+/// prefixed by given indentation
+fn stringify_files(dir:&Directory, indentation:&str) -> String {
+    let mut resulting_string = String::new();
+    for file in  dir.files.iter().filter(|file| !dir.is_folder_note(file)){
+        // skipping if extension is mismatching
+        if let FileExtension::Markdown = file.extension {
+            let file_link:String = format!("{indentation}- [{}]({})\n",escape_title(&file.title),chapter_link(file));
+            resulting_string.push_str(&file_link)
+        }
+    };
+    resulting_string
+}
+
+/// escapes brackets, which would end the link text of a chapter
+fn escape_title(title:&str) -> String {
+    title.replace('[', "\\[").replace(']', "\\]")
+}
+
+/// draft chapters are denoted by an empty link
+fn chapter_link(file:&FileData) -> String {
+    match file.draft {
+        true => String::new(),
+        false => summary_link(&file.relative_dest_path),
+    }
+}
+
+/// takes Directory checks whether any .md file is contained in top-level folder 
+/// returns True if one was found 
+/// false otherwise
+fn contains_md_file(directory:&Directory) -> bool { 
+    for file in &directory.files{
+        if let FileExtension::Markdown = file.extension {
+            return true;
+        }
+    }
+    false
+}
+
+/// checks whether any .md file is contained in the directory or its sub directories
+fn contains_md_file_recursive(directory:&Directory) -> bool {
+    contains_md_file(directory) || directory.sub_directories.iter().any(contains_md_file_recursive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontmatter::Frontmatter;
+
+    fn note(dir: &str, name: &str) -> FileData {
+        let relative_path = PathBuf::from(dir).join(name);
+        FileData {
+            original_path: relative_path.clone(),
+            dest_path: relative_path.clone(),
+            relative_path: relative_path.clone(),
+            relative_dest_path: relative_path,
+            extension: FileExtension::Markdown,
+            name: name.to_string(),
+            title: name.trim_end_matches(".md").to_string(),
+            frontmatter: Frontmatter::default(),
+            draft: false,
+        }
+    }
+
+    fn directory(path: &str, files: Vec<FileData>, sub_directories: Vec<Directory>) -> Directory {
+        let name = path.rsplit('/').next().unwrap_or_default().to_string();
+        Directory {
+            path: PathBuf::from(path),
+            title: name.clone(),
+            name,
+            dest_path: PathBuf::from(path),
+            relative_path: PathBuf::from(path),
+            relative_dest_path: PathBuf::from(path),
+            sub_directories,
+            files,
+            folder_note: None,
+            order: None,
+        }
+    }
+
+    #[test]
+    fn nests_directories_as_chapters() {
+        let deeper = directory("guide/advanced", vec![note("guide/advanced", "tuning.md")], vec![]);
+        let guide = directory("guide", vec![note("guide", "setup.md")], vec![deeper]);
+        let empty = directory("assets", vec![], vec![]);
+        let root = directory("", vec![note("", "intro.md")], vec![guide, empty]);
+
+        let nested = nest_directory(&root, 0, &OutputSettings::default());
+        assert_eq!(
+            nested,
+            "- [intro](intro.md)\n\
+- [guide](guide/index.md)\n    - [setup](guide/setup.md)\n    \
+- [advanced](guide/advanced/index.md)\n        - [tuning](guide/advanced/tuning.md)\n"
+        );
+
+        // without index pages directory chapters become drafts
+        let settings = OutputSettings { index_pages: false, ..OutputSettings::default() };
+        assert!(nest_directory(&root, 0, &settings).contains("- [guide]()\n"));
+    }
+
+    #[test]
+    fn folder_notes_back_their_directory_chapter() {
+        let mut guide = directory("guide", vec![note("guide", "setup.md"), note("guide", "guide.md")], vec![]);
+        guide.folder_note = find_folder_note(&guide);
+        let root = directory("", vec![], vec![guide]);
+
+        let nested = nest_directory(&root, 0, &OutputSettings::default());
+        assert_eq!(nested, "- [guide](guide/guide.md)\n    - [setup](guide/setup.md)\n");
+    }
+
+    #[test]
+    fn generated_index_never_replaces_a_note() {
+        let guide = directory("guide", vec![note("guide", "setup.md")], vec![]);
+        assert_eq!(generated_index_path(&guide), PathBuf::from("guide/index.md"));
+        // e.g. with `folder_notes = false`, "index.md" is copied as a regular note
+        let guide = directory("guide", vec![note("guide", "index.md")], vec![]);
+        assert_eq!(generated_index_path(&guide), PathBuf::from("guide/_index.md"));
+        let settings = OutputSettings { folder_notes: false, ..OutputSettings::default() };
+        let nested = nest_directory(&directory("", vec![], vec![guide]), 0, &settings);
+        assert_eq!(nested, "- [guide](guide/_index.md)\n    - [index](guide/index.md)\n");
+    }
+}
@@ -0,0 +1,297 @@
+//! contains logic to write the collected files to the destination
+//!
+//! every file written is recorded in a manifest (`.obs2mdbook-manifest.toml`) inside of the destination.
+//! in the incremental mode (`--sync` or `sync = true` in the `[output]` section of the project file):
+//! - converted notes and generated pages are only written if their content changed (hash)
+//! - other files are only copied if size or modification time of the source changed
+//! - files recorded in the manifest that are no longer collected are deleted,
+//!   files not created by this tool are never touched
+//!
+//! without it every file is written, but nothing is deleted.
+//! stale files stay recorded in the manifest though, a later incremental run still deletes them
+
+// internal imports
+use crate::diagnostics::{Diagnostics, ObsError};
+
+// external imports
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub const MANIFEST_FILE: &str = ".obs2mdbook-manifest.toml";
+
+/// content of a single file in the destination
+pub enum OutputSource {
+    // converted note or generated page
+    Content(String),
+    // file copied as it is
+    Copy(PathBuf),
+}
+
+/// single file to be placed in the destination
+pub struct PlannedFile {
+    pub relative_dest_path: PathBuf,
+    pub source: OutputSource,
+    // file or directory inside of the vault the file is created from
+    pub origin: PathBuf,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    Create,
+    Overwrite,
+    Unchanged,
+}
+
+/// every change necessary to bring the destination up to date
+pub struct SyncPlan {
+    pub dest_dir: PathBuf,
+    pub files: Vec<(PlannedFile, Action)>,
+    // destination-relative paths of stale files created by an earlier run
+    pub deletions: Vec<PathBuf>,
+    manifest: Manifest,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+struct Manifest {
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq)]
+#[serde(default)]
+struct ManifestEntry {
+    // hash of written content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    // size and modification time of a copied source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+}
+
+// ---
+// CORE FUNCTIONS
+// ---
+
+/// compares the planned files with the manifest of the destination
+/// files whose source can not be read are reported and skipped,
+/// as are files mapped to the destination of an earlier one (e.g. "My Note.md" and "my-note.md" with kebab)
+pub fn plan_sync(
+    files: Vec<PlannedFile>,
+    dest_dir: &Path,
+    incremental: bool,
+    diagnostics: &mut Diagnostics,
+) -> Result<SyncPlan, ObsError> {
+    let previous = read_manifest(dest_dir)?;
+    let mut manifest = Manifest::default();
+    let mut planned = Vec::new();
+    let mut origins: HashMap<String, PathBuf> = HashMap::new();
+
+    for file in files {
+        let key = manifest_key(&file.relative_dest_path);
+        if let Some(first) = origins.get(&key) {
+            let message = format!(
+                "destination \"{}\" is already taken by \"{}\", file is skipped",
+                file.relative_dest_path.display(),
+                first.display()
+            );
+            diagnostics.report(ObsError::traversal(message).at(&file.origin))?;
+            continue;
+        }
+        origins.insert(key.clone(), file.origin.clone());
+        let entry = match manifest_entry(&file.source) {
+            Ok(entry) => entry,
+            Err(error) => {
+                diagnostics.report(error)?;
+                continue;
+            }
+        };
+        let exists = dest_dir.join(&file.relative_dest_path).is_file();
+        let action = match previous.files.get(&key) {
+            _ if !exists => Action::Create,
+            Some(previous_entry) if incremental && *previous_entry == entry => Action::Unchanged,
+            _ => Action::Overwrite,
+        };
+        manifest.files.insert(key, entry);
+        planned.push((file, action));
+    }
+
+    let stale: Vec<(String, ManifestEntry)> = previous
+        .files
+        .into_iter()
+        .filter(|(key, _)| !manifest.files.contains_key(key))
+        // never leaving the destination, even if the manifest was tampered with
+        .filter(|(key, _)| Path::new(key).components().all(|component| matches!(component, Component::Normal(_))))
+        .filter(|(key, _)| dest_dir.join(key).is_file())
+        .collect();
+    let deletions = match incremental {
+        true => stale.into_iter().map(|(key, _)| PathBuf::from(key)).collect(),
+        // kept in the manifest, so a later incremental run still deletes them
+        false => {
+            manifest.files.extend(stale);
+            Vec::new()
+        }
+    };
+    Ok(SyncPlan {
+        dest_dir: dest_dir.to_path_buf(),
+        files: planned,
+        deletions,
+        manifest,
+    })
+}
+
+/// writes, copies and deletes files as planned, then updates the manifest
+/// failing files are reported but do not stop the sync
+pub fn execute_plan(plan: &SyncPlan, diagnostics: &mut Diagnostics) -> Result<(), ObsError> {
+    let mut manifest = plan.manifest.clone();
+    for (file, action) in &plan.files {
+        if *action == Action::Unchanged {
+            continue;
+        }
+        let dest_path = plan.dest_dir.join(&file.relative_dest_path);
+        if let Err(error) = write_file(&dest_path, &file.source) {
+            // retried on the next run
+            manifest.files.remove(&manifest_key(&file.relative_dest_path));
+            diagnostics.report(ObsError::io(format!("could not write file\n {error}")).at(&dest_path))?;
+        }
+    }
+    for relative_path in &plan.deletions {
+        let dest_path = plan.dest_dir.join(relative_path);
+        match fs::remove_file(&dest_path) {
+            Ok(_) => remove_empty_parents(&dest_path, &plan.dest_dir),
+            Err(error) => diagnostics.report(ObsError::io(format!("could not remove file\n {error}")).at(&dest_path))?,
+        }
+    }
+    let manifest_path = plan.dest_dir.join(MANIFEST_FILE);
+    let content = toml::to_string(&manifest).map_err(|e| ObsError::io(e).at(&manifest_path))?;
+    fs::create_dir_all(&plan.dest_dir)
+        .and_then(|_| fs::write(&manifest_path, content))
+        .map_err(|e| ObsError::io(format!("could not write manifest\n {e}")).at(&manifest_path))
+}
+
+/// prints every added, updated and removed file followed by their count
+pub fn print_plan_summary(plan: &SyncPlan) {
+    let count = |wanted: Action| plan.files.iter().filter(|(_, action)| *action == wanted).count();
+    for (file, action) in &plan.files {
+        match action {
+            Action::Create => println!("+ {}", file.relative_dest_path.display()),
+            Action::Overwrite => println!("~ {}", file.relative_dest_path.display()),
+            Action::Unchanged => {}
+        }
+    }
+    for relative_path in &plan.deletions {
+        println!("- {}", relative_path.display());
+    }
+    println!(
+        "added: {}, updated: {}, removed: {}, unchanged: {}",
+        count(Action::Create),
+        count(Action::Overwrite),
+        plan.deletions.len(),
+        count(Action::Unchanged)
+    );
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+fn read_manifest(dest_dir: &Path) -> Result<Manifest, ObsError> {
+    let path = dest_dir.join(MANIFEST_FILE);
+    if !path.is_file() {
+        return Ok(Manifest::default());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| ObsError::io(e).at(&path))?;
+    toml::from_str(&content).map_err(|e| ObsError::io(format!("could not parse manifest\n {e}")).at(&path))
+}
+
+fn manifest_entry(source: &OutputSource) -> Result<ManifestEntry, ObsError> {
+    match source {
+        OutputSource::Content(content) => Ok(ManifestEntry {
+            hash: Some(format!("{:016x}", fnv1a(content.as_bytes()))),
+            ..ManifestEntry::default()
+        }),
+        OutputSource::Copy(path) => {
+            let metadata = fs::metadata(path).map_err(|e| ObsError::io(e).at(path))?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs());
+            Ok(ManifestEntry {
+                size: Some(metadata.len()),
+                modified,
+                ..ManifestEntry::default()
+            })
+        }
+    }
+}
+
+fn write_file(dest_path: &Path, source: &OutputSource) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match source {
+        OutputSource::Content(content) => fs::write(dest_path, content)?,
+        OutputSource::Copy(path) => {
+            fs::copy(path, dest_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// removes directories left empty by a deletion, up to the destination
+fn remove_empty_parents(path: &Path, dest_dir: &Path) {
+    for parent in path.ancestors().skip(1) {
+        if parent == dest_dir || !parent.starts_with(dest_dir) || fs::remove_dir(parent).is_err() {
+            break;
+        }
+    }
+}
+
+/// "notes\sub\a.md" --> "notes/sub/a.md", equal on every platform
+fn manifest_key(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 64-bit FNV-1a, stable across platforms and compiler versions unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::ErrorKind;
+
+    fn planned(relative_dest_path: &str, origin: &str) -> PlannedFile {
+        PlannedFile {
+            relative_dest_path: PathBuf::from(relative_dest_path),
+            source: OutputSource::Content(format!("# {origin}\n")),
+            origin: PathBuf::from(origin),
+        }
+    }
+
+    #[test]
+    fn skips_files_mapped_to_a_taken_destination() {
+        let dest_dir = std::env::temp_dir().join(format!("obs2mdbook-missing-dest-{}", std::process::id()));
+        let files = || vec![planned("my-note.md", "My Note.md"), planned("my-note.md", "my-note.md")];
+
+        let plan = plan_sync(files(), &dest_dir, false, &mut Diagnostics::new(false)).unwrap();
+        assert_eq!(plan.files.len(), 1);
+        assert_eq!(plan.files[0].0.origin, PathBuf::from("My Note.md"));
+
+        let error = plan_sync(files(), &dest_dir, false, &mut Diagnostics::new(true)).err().unwrap();
+        assert_eq!(error.kind, ErrorKind::Traversal);
+        assert_eq!(error.path, Some(PathBuf::from("my-note.md")));
+    }
+}
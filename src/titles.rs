@@ -0,0 +1,252 @@
+//! contains logic to determine the title of a chapter
+//!
+//! titles are resolved in the following order:
+//! 1. `title` property of the frontmatter
+//! 2. first level-one heading (`# Heading`) of the note
+//! 3. prettified file name: "121-130_my_note.md" --> "121-130 my note"
+//!    (or "my note" if number prefixes are stripped)
+//!
+//! directory headings are the prettified directory name.
+//! both are transformed by the rules of the `prefixes_for_headlines` config section,
+//! every entry is a single rule:
+//! - `Part`: plain prefix, the n-th plain entry prefixes directory headings at depth n
+//! - `depth 2: Chapter`: prefixes directory headings at the given depth
+//! - `notes/lectures/*: Lecture`: prefixes headings of directories and titles of notes matching the
+//!   gitignore-style pattern, takes precedence over prefixes by depth
+//! - `strip: ^\d+ `: regex removed from directory headings and chapter titles
+//!
+//! the depth of a directory is the number of components of its vault-relative path,
+//! top-level directories have depth 1. prefixes by depth (and those of override files)
+//! only apply to directory headings. prefix and heading are separated by a space
+
+// internal imports
+use crate::frontmatter::Frontmatter;
+use crate::overrides::DirectoryOverrides;
+use crate::patterns::PatternSet;
+use crate::structures::{Config, ConfigType};
+
+// external imports
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// johnny-decimal like number prefixes: "121-130_", "121_", "11.01 ", "000-099_"
+static NUMBER_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\d+(?:[-.]\d+)?[_\s-]+").unwrap());
+
+pub const STRIP_RULE: &str = "strip";
+const DEPTH_RULE: &str = "depth ";
+
+/// transformation of headings and titles, set in `prefixes_for_headlines`
+#[derive(Default)]
+pub struct HeadlineRules {
+    strip: Vec<Regex>,
+    depth_prefixes: BTreeMap<usize, String>,
+    // checked in reverse order, the last matching pattern wins
+    directory_prefixes: Vec<(PatternSet, String)>,
+}
+
+impl HeadlineRules {
+    /// collects the rules of every `prefixes_for_headlines` section in order
+    pub fn from_configs(configurations: &[Config]) -> Result<HeadlineRules, Box<dyn Error>> {
+        let mut rules = HeadlineRules::default();
+        let mut plain_depth = 0;
+        let entries = configurations
+            .iter()
+            .filter(|config| matches!(config.conf_type, ConfigType::PrefixHeadline))
+            .flat_map(|config| &config.collection_of_options);
+
+        for entry in entries {
+            let entry = entry.trim();
+            let rule = entry
+                .split_once(':')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .filter(|(key, value)| !key.is_empty() && !value.is_empty());
+            match rule {
+                Some((STRIP_RULE, pattern)) => {
+                    let regex = Regex::new(pattern)
+                        .map_err(|e| format!("invalid strip pattern \"{pattern}\"\n {e}"))?;
+                    rules.strip.push(regex);
+                }
+                Some((key, prefix)) if key.starts_with(DEPTH_RULE) => {
+                    let depth = key[DEPTH_RULE.len()..]
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|e| format!("invalid depth in \"{entry}\"\n {e}"))?;
+                    rules.depth_prefixes.insert(depth, prefix.to_string());
+                }
+                Some((pattern, prefix)) => {
+                    let mut patterns = PatternSet::default();
+                    patterns.push(pattern)?;
+                    rules.directory_prefixes.push((patterns, prefix.to_string()));
+                }
+                None => {
+                    plain_depth += 1;
+                    rules.depth_prefixes.insert(plain_depth, entry.to_string());
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    /// heading of a directory: prettified name, stripped and prefixed
+    /// title and prefix of an override file take precedence
+    ///
+    /// EXAMPLE:
+    /// with `notes/*: Topic` and `strip: ^\d+ `
+    /// "notes/121_machine_learning" --> "Topic machine learning"
+    pub fn directory_title(&self, relative_path: &Path, name: &str, strip_number_prefix: bool, overrides: &DirectoryOverrides) -> String {
+        let title = match &overrides.title {
+            Some(title) => title.clone(),
+            None => self.strip(&prettify_name(name, strip_number_prefix)),
+        };
+        let depth = relative_path.components().count();
+        let prefix = overrides.prefix.as_ref().or_else(|| {
+            self.directory_prefixes
+                .iter()
+                .rev()
+                .find(|(patterns, _)| patterns.matches(relative_path, true))
+                .map(|(_, prefix)| prefix)
+                .or_else(|| self.depth_prefixes.get(&depth))
+        });
+        match prefix {
+            Some(prefix) => format!("{prefix} {title}"),
+            None => title,
+        }
+    }
+
+    /// title of a note: resolved title, stripped and prefixed by the last matching pattern
+    /// patterns without extension match notes as well, directory-only patterns never match
+    ///
+    /// EXAMPLE:
+    /// with `notes/lectures/*: Lecture`
+    /// "notes/lectures/week_1.md" titled "Week 1" --> "Lecture Week 1"
+    pub fn file_title(&self, relative_path: &Path, title: &str) -> String {
+        let title = self.strip(title);
+        // "notes/intro" should match "notes/intro.md" as well
+        let stem = relative_path.with_extension("");
+        let prefix = self
+            .directory_prefixes
+            .iter()
+            .rev()
+            .find(|(patterns, _)| patterns.matches(relative_path, false) || patterns.matches(&stem, false))
+            .map(|(_, prefix)| prefix);
+        match prefix {
+            Some(prefix) => format!("{prefix} {title}"),
+            None => title,
+        }
+    }
+
+    /// removes every strip pattern from given title, keeps it if nothing would remain
+    pub fn strip(&self, title: &str) -> String {
+        let stripped = self
+            .strip
+            .iter()
+            .fold(title.to_string(), |title, regex| regex.replace_all(&title, "").into_owned());
+        match stripped.trim().is_empty() {
+            true => title.to_string(),
+            false => stripped.trim().to_string(),
+        }
+    }
+}
+
+/// resolves the title of a markdown note
+pub fn resolve_title(path: &Path, name: &str, frontmatter: &Frontmatter, strip_number_prefix: bool) -> String {
+    if let Some(title) = frontmatter.get_text("title").filter(|title| !title.trim().is_empty()) {
+        return title.trim().to_string();
+    }
+    if let Some(heading) = read_first_heading(path) {
+        return heading;
+    }
+    prettify_name(name, strip_number_prefix)
+}
+
+/// removes the extension and replaces "_" and "-" with spaces
+pub fn prettify_name(name: &str, strip_number_prefix: bool) -> String {
+    let stem = name.strip_suffix(".md").unwrap_or(name);
+    let stem = match strip_number_prefix {
+        true => NUMBER_PREFIX.replace(stem, ""),
+        false => stem.into(),
+    };
+    let pretty = stem
+        .split(['_', '-'])
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    match pretty.is_empty() {
+        true => name.to_string(),
+        false => pretty,
+    }
+}
+
+/// returns the first level-one heading of the note
+/// frontmatter and fenced code blocks are skipped
+fn read_first_heading(path: &Path) -> Option<String> {
+    let reader = BufReader::new(File::open(path).ok()?);
+    let mut in_frontmatter = false;
+    let mut fence: Option<&str> = None;
+
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line.ok()?;
+        let trimmed = line.trim();
+        if line_number == 0 && trimmed == "---" {
+            in_frontmatter = true;
+            continue;
+        }
+        if in_frontmatter {
+            in_frontmatter = trimmed != "---";
+            continue;
+        }
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|marker| trimmed.starts_with(marker)) {
+            fence = Some(marker);
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("# ") {
+            let heading = heading.trim().trim_end_matches('#').trim();
+            if !heading.is_empty() {
+                return Some(heading.to_string());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(entries: &[&str]) -> HeadlineRules {
+        let config = Config {
+            conf_type: ConfigType::PrefixHeadline,
+            collection_of_options: entries.iter().map(|entry| entry.to_string()).collect(),
+        };
+        HeadlineRules::from_configs(&[config]).unwrap()
+    }
+
+    #[test]
+    fn prefixes_notes_matching_a_pattern() {
+        let rules = rules(&["Part", "notes/lectures/*: Lecture", "notes/intro: Welcome", "strip: ^\\d+ "]);
+        assert_eq!(rules.file_title(Path::new("notes/lectures/week_1.md"), "01 Week 1"), "Lecture Week 1");
+        assert_eq!(rules.file_title(Path::new("notes/intro.md"), "Intro"), "Welcome Intro");
+        // neither prefixes by depth nor prefixes of parent directories apply to notes
+        assert_eq!(rules.file_title(Path::new("top.md"), "Top"), "Top");
+        assert_eq!(rules.file_title(Path::new("notes/lectures/week_1/extra.md"), "Extra"), "Extra");
+    }
+
+    #[test]
+    fn directory_only_patterns_never_prefix_notes() {
+        let rules = rules(&["notes/*/: Topic"]);
+        assert_eq!(rules.file_title(Path::new("notes/a.md"), "A"), "A");
+        let title = rules.directory_title(Path::new("notes/a"), "a", false, &DirectoryOverrides::default());
+        assert_eq!(title, "Topic a");
+    }
+}
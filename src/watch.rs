@@ -0,0 +1,111 @@
+//! contains logic to watch the vault for changes
+//!
+//! events arriving in quick succession (saving several notes, moving a directory)
+//! are collected into a single change set, so that each burst causes one rebuild only.
+//! config files (the config note, the notes it includes and the project file) are watched
+//! as well, also if they lie outside of the vault
+
+// internal imports
+use crate::diagnostics::ObsError;
+
+// external imports
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// time without events after which a burst of changes is considered finished
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// every path changed within one burst of events
+pub struct ChangeSet {
+    pub paths: Vec<PathBuf>,
+    // whether one of the config files is among the changed paths
+    pub config_changed: bool,
+}
+
+/// watches the vault and the given config files, calls `on_change` after every burst of changes.
+/// `on_change` returns the config files after reloading them, which are watched from then on
+/// only returns if watching is not possible
+pub fn watch_vault(
+    vault: &Path,
+    config_files: &[PathBuf],
+    mut on_change: impl FnMut(&ChangeSet) -> Option<Vec<PathBuf>>,
+) -> Result<(), ObsError> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    watcher
+        .watch(vault, RecursiveMode::Recursive)
+        .map_err(|e| watch_error(e).at(vault))?;
+
+    let vault = vault.canonicalize().unwrap_or(vault.to_path_buf());
+    let mut watched_dirs = HashSet::new();
+    let mut config_files = canonical_paths(config_files);
+    watch_config_dirs(&mut watcher, &vault, &config_files, &mut watched_dirs)?;
+    println!("watching {} for changes, press ctrl-c to stop", vault.display());
+
+    // the channel only closes if the watcher is dropped
+    while let Ok(first) = receiver.recv() {
+        let mut events = vec![first];
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE) {
+            events.push(event);
+        }
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for event in events {
+            match event {
+                Ok(event) if !matches!(event.kind, EventKind::Access(_)) => paths.extend(event.paths),
+                Ok(_) => {}
+                Err(error) => println!("failed to receive change with following error \n {error}"),
+            }
+        }
+        paths.sort();
+        paths.dedup();
+        if paths.is_empty() {
+            continue;
+        }
+        let config_changed = paths
+            .iter()
+            .any(|path| config_files.contains(&path.canonicalize().unwrap_or(path.clone())));
+        if let Some(reloaded) = on_change(&ChangeSet { paths, config_changed }) {
+            config_files = canonical_paths(&reloaded);
+            watch_config_dirs(&mut watcher, &vault, &config_files, &mut watched_dirs)?;
+        }
+    }
+    Ok(())
+}
+
+// ----
+// HELPER FUNCTIONS
+// ----
+
+fn watch_error(error: notify::Error) -> ObsError {
+    ObsError::io(format!("could not watch for changes\n {error}"))
+}
+
+fn canonical_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .map(|path| path.canonicalize().unwrap_or(path.clone()))
+        .collect()
+}
+
+/// config files outside of the vault are watched through their directory, as editors often replace files
+/// `watched_dirs` holds the directories watched so far, each one is only added once
+fn watch_config_dirs(
+    watcher: &mut RecommendedWatcher,
+    vault: &Path,
+    config_files: &[PathBuf],
+    watched_dirs: &mut HashSet<PathBuf>,
+) -> Result<(), ObsError> {
+    for config_dir in config_files.iter().filter_map(|path| path.parent()) {
+        if config_dir.starts_with(vault) || watched_dirs.contains(config_dir) {
+            continue;
+        }
+        watcher
+            .watch(config_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| watch_error(e).at(config_dir))?;
+        watched_dirs.insert(config_dir.to_path_buf());
+    }
+    Ok(())
+}
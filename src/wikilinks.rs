@@ -0,0 +1,116 @@
+//! contains logic to convert obsidian wikilinks to mdbook-compatible relative links
+//!
+//! supported notations:
+//! - `[[Some Note]]` --> `[Some Note](relative/path/Some Note.md)`
+//! - `[[Some Note|alias]]` --> `[alias](relative/path/Some Note.md)`
+//! - `[[Some Note#Heading]]` --> `[Some Note > Heading](relative/path/Some Note.md#heading)`
+//! - `[[#Heading]]` --> `[Heading](#heading)`
+//!
+//! embeds (`![[...]]`) share the notation and are handed to embeds.rs
+//! links that cannot be resolved are replaced by their display text
+
+// internal imports
+use crate::content::ConversionContext;
+use crate::embeds::convert_embed;
+use crate::link_resolver::{heading_to_anchor, relative_link};
+
+// external imports
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// matches wikilinks and embeds alike, the latter are told apart by the "!"
+static WIKILINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(!?)\[\[([^\[\]\n]+?)\]\]").unwrap());
+/// quote markers at the start of a line: "> ", "> > ", "  >"
+static QUOTE_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:[ \t]*>)+[ \t]?").unwrap());
+
+/// parsed representation of the inner part of a wikilink
+pub struct WikiLink<'a> {
+    pub target: &'a str,
+    pub heading: Option<&'a str>,
+    pub alias: Option<&'a str>,
+}
+
+/// splits "target#heading|alias" into its parts
+/// nested headings ("Note#H1#H2") keep only the last heading
+/// "\|" is accepted as separator as well, as used inside tables
+pub fn parse_wikilink(inner: &str) -> WikiLink<'_> {
+    let (link, alias) = match inner.split_once('|') {
+        Some((link, alias)) => (link.strip_suffix('\\').unwrap_or(link), Some(alias.trim())),
+        None => (inner, None),
+    };
+    let (target, heading) = match link.split_once('#') {
+        Some((target, headings)) => (target.trim(), headings.rsplit('#').next().map(str::trim)),
+        None => (link.trim(), None),
+    };
+    WikiLink {
+        target,
+        heading: heading.filter(|heading| !heading.is_empty()),
+        alias: alias.filter(|alias| !alias.is_empty()),
+    }
+}
+
+/// replaces every wikilink in the given text with a markdown link
+/// and every embed with its converted representation
+/// embeds inside of quotes or callouts are quoted as well, so that they do not end the quote
+pub fn convert_wikilinks(text: &str, context: &ConversionContext) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for caps in WIKILINK.captures_iter(text) {
+        let whole = caps.get(0).unwrap();
+        result.push_str(&text[last_end..whole.start()]);
+        last_end = whole.end();
+
+        let link = parse_wikilink(&caps[2]);
+        if &caps[1] != "!" {
+            result.push_str(&convert_single_link(&link, context));
+            continue;
+        }
+        let embed = convert_embed(&link, context);
+        let line_start = text[..whole.start()].rfind('\n').map(|index| index + 1).unwrap_or(0);
+        match QUOTE_PREFIX.find(&text[line_start..whole.start()]) {
+            Some(prefix) => result.push_str(&embed.replace('\n', &format!("\n{}", prefix.as_str()))),
+            None => result.push_str(&embed),
+        }
+    }
+    result.push_str(&text[last_end..]);
+    result
+}
+
+fn convert_single_link(link: &WikiLink, context: &ConversionContext) -> String {
+    let display = display_text(link);
+    // block references ("^block-id") have no counterpart in mdbook
+    let anchor = link
+        .heading
+        .filter(|heading| !heading.starts_with('^'))
+        .map(|heading| format!("#{}", heading_to_anchor(heading)))
+        .unwrap_or_default();
+
+    if link.target.is_empty() {
+        // links to headings of transcluded notes have to point to the note itself
+        if let Some(source) = context.index.get(context.source).filter(|_| context.is_transcluded()) {
+            let destination = relative_link(context.output, &source.relative_dest_path);
+            return format!("[{display}]({destination}{anchor})");
+        }
+        return format!("[{display}]({anchor})");
+    }
+    match context.index.resolve(link.target, context.source) {
+        Some(resolved) => {
+            let destination = relative_link(context.output, &resolved.relative_dest_path);
+            format!("[{display}]({destination}{anchor})")
+        }
+        None => {
+            context.warn(format!("could not resolve link [[{}]]", link.target));
+            display
+        }
+    }
+}
+
+/// text shown for the link, mirrors what obsidian displays
+pub fn display_text(link: &WikiLink) -> String {
+    match (link.alias, link.heading) {
+        (Some(alias), _) => alias.to_string(),
+        (None, Some(heading)) if link.target.is_empty() => heading.to_string(),
+        (None, Some(heading)) => format!("{} > {}", link.target, heading),
+        (None, None) => link.target.to_string(),
+    }
+}